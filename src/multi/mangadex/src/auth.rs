@@ -0,0 +1,453 @@
+use std::sync::Mutex;
+
+use miniserde::{Deserialize, Serialize};
+
+use crate::host_settings::HostSettings;
+use crate::host_storage::HostStorage;
+use crate::schema::auth::TokenResponseSchema;
+use crate::utils::clock;
+
+#[cfg_attr(test, allow(dead_code))]
+const AUTH_URL: &str = "https://auth.mangadex.org/realms/mangadex/protocol/openid-connect/token";
+
+/// The tokens are renewed this many seconds before they expire, so that they
+/// don't expire while a request is in flight.
+const EXPIRY_MARGIN: u32 = 60;
+
+/// The storage key of the session.
+const SESSION_KEY: &str = "auth_session";
+
+/// Send a form to the token endpoint.
+#[doc(hidden)]
+macro_rules! token_request {
+    ($form:expr) => {{
+        #[cfg(not(test))]
+        let token_response = post_token_request($form);
+        #[cfg(test)]
+        let token_response = tests::token_endpoint($form);
+        token_response
+    }};
+}
+
+/// The personal API client and account used to log in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    /// Get the credentials from the settings, if they are all set.
+    pub fn from_settings() -> Option<Self> {
+        let credentials = Credentials {
+            client_id: HostSettings::get_client_id(),
+            client_secret: HostSettings::get_client_secret(),
+            username: HostSettings::get_username(),
+            password: HostSettings::get_password(),
+        };
+
+        let is_complete = !credentials.client_id.is_empty()
+            && !credentials.client_secret.is_empty()
+            && !credentials.username.is_empty()
+            && !credentials.password.is_empty();
+
+        is_complete.then_some(credentials)
+    }
+}
+
+/// The tokens of the logged in account, along with the client and account
+/// they were issued to. The secrets are never stored.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct Session {
+    client_id: String,
+    username: String,
+    access_token: String,
+    refresh_token: String,
+    expires_at: u32,
+    refresh_expires_at: u32,
+}
+
+impl Session {
+    /// Whether the session was issued to the given client and account.
+    fn is_issued_to(&self, credentials: &Credentials) -> bool {
+        self.client_id == credentials.client_id && self.username == credentials.username
+    }
+}
+
+/// The session of the logged in account, loaded from the host storage when
+/// the instance starts.
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// The credentials the latest login failed with. The requests that don't
+/// need an account don't try to log in with them again.
+static FAILED_CREDENTIALS: Mutex<Option<Credentials>> = Mutex::new(None);
+
+/// Get a valid access token, logging in or refreshing the session when
+/// needed.
+///
+/// Returns `None` if no credentials are configured.
+pub fn access_token() -> Result<Option<String>, ()> {
+    let Some(credentials) = Credentials::from_settings() else {
+        return Ok(None);
+    };
+
+    let mut session = SESSION.lock().map_err(|_| ())?;
+    if session.is_none() {
+        *session = load_session();
+    }
+
+    // The session of another account or client is of no use
+    if session
+        .as_ref()
+        .is_some_and(|session| !session.is_issued_to(&credentials))
+    {
+        *session = None;
+        let _ = HostStorage::delete(SESSION_KEY);
+    }
+
+    let previous_access_token = session.as_ref().map(|session| session.access_token.clone());
+    let access_token = session_access_token(&mut session, &credentials, clock::now);
+
+    if let Ok(mut failed_credentials) = FAILED_CREDENTIALS.lock() {
+        *failed_credentials = access_token.is_err().then(|| credentials.clone());
+    }
+    let access_token = access_token?;
+
+    // Keep the renewed tokens for the next instances
+    if previous_access_token.as_ref() != Some(&access_token) {
+        if let Some(session) = session.as_ref() {
+            save_session(session, clock::now());
+        }
+    }
+
+    Ok(Some(access_token))
+}
+
+/// Get a valid access token for the requests that don't need an account.
+///
+/// Unlike `access_token`, returns `None` if the account can't be logged in,
+/// and doesn't log in again with the credentials that already failed.
+pub fn optional_access_token() -> Option<String> {
+    let failed_credentials = FAILED_CREDENTIALS.lock().ok()?.clone();
+    if failed_credentials.is_some() && failed_credentials == Credentials::from_settings() {
+        return None;
+    }
+
+    access_token().ok().flatten()
+}
+
+/// Consider the access token of the session expired, for example when the
+/// API rejected it, so that it is renewed by the next `access_token` call.
+pub fn expire_access_token() {
+    if let Ok(mut session) = SESSION.lock() {
+        if let Some(session) = session.as_mut() {
+            session.expires_at = 0;
+        }
+    }
+}
+
+fn load_session() -> Option<Session> {
+    let bytes = HostStorage::get(SESSION_KEY)?;
+    let content = std::str::from_utf8(&bytes).ok()?;
+
+    miniserde::json::from_str(content).ok()
+}
+
+fn save_session(session: &Session, now: Option<u32>) {
+    // The stored session is of no use once the refresh token expired
+    let ttl_seconds = now.map(|now| session.refresh_expires_at.saturating_sub(now));
+    let content = miniserde::json::to_string(session);
+
+    // The session still lives in memory if it can't be stored
+    let _ = HostStorage::set(SESSION_KEY, content.as_bytes(), ttl_seconds);
+}
+
+fn session_access_token(
+    session: &mut Option<Session>,
+    credentials: &Credentials,
+    now: fn() -> Option<u32>,
+) -> Result<String, ()> {
+    if session
+        .as_ref()
+        .is_some_and(|session| !session.is_issued_to(credentials))
+    {
+        *session = None;
+    }
+
+    let is_valid =
+        |expires_at: u32| now().is_some_and(|now| now.saturating_add(EXPIRY_MARGIN) < expires_at);
+
+    let token_response = match session.as_ref() {
+        Some(session) if is_valid(session.expires_at) => {
+            return Ok(session.access_token.clone());
+        }
+        // Fall back to logging in again if the refresh token was revoked. A
+        // stored session is refreshed even before the clock is known.
+        Some(session) if now().is_none() || is_valid(session.refresh_expires_at) => {
+            refresh(credentials, &session.refresh_token).or_else(|_| login(credentials))?
+        }
+        _ => login(credentials)?,
+    };
+
+    // The clock may only be known once the token endpoint answered
+    let now = now().unwrap_or_default();
+    let access_token = token_response.access_token.clone();
+
+    *session = Some(Session {
+        client_id: credentials.client_id.clone(),
+        username: credentials.username.clone(),
+        access_token: token_response.access_token,
+        refresh_token: token_response.refresh_token,
+        expires_at: now.saturating_add(token_response.expires_in),
+        refresh_expires_at: now.saturating_add(token_response.refresh_expires_in),
+    });
+
+    Ok(access_token)
+}
+
+fn login(credentials: &Credentials) -> Result<TokenResponseSchema, ()> {
+    token_request!(&[
+        ("grant_type", "password"),
+        ("username", &credentials.username),
+        ("password", &credentials.password),
+        ("client_id", &credentials.client_id),
+        ("client_secret", &credentials.client_secret),
+    ])
+}
+
+fn refresh(credentials: &Credentials, refresh_token: &str) -> Result<TokenResponseSchema, ()> {
+    token_request!(&[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", &credentials.client_id),
+        ("client_secret", &credentials.client_secret),
+    ])
+}
+
+#[cfg(not(test))]
+fn post_token_request(form: &[(&str, &str)]) -> Result<TokenResponseSchema, ()> {
+    use crate::bindings::midoku::http::outgoing_handler::{handle, Method};
+
+    let body = encode_form(form);
+
+    let headers = vec![
+        ("User-Agent".to_string(), HostSettings::get_user_agent()),
+        (
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        ),
+    ];
+    let response = handle(
+        Method::Post,
+        AUTH_URL,
+        Some(&headers),
+        Some(body.as_bytes()),
+    )?;
+    clock::observe_date_header(&response.headers());
+
+    parse_token_response(response.status_code(), &response.bytes())
+}
+
+/// Encode a form as `application/x-www-form-urlencoded`.
+fn encode_form(form: &[(&str, &str)]) -> String {
+    use crate::utils::url_encode::url_encode;
+
+    form.iter()
+        .map(|(name, value)| format!("{}={}", url_encode(name), url_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Parse the response of the token endpoint, which answers with an error
+/// object when the credentials are rejected.
+fn parse_token_response(status_code: u16, bytes: &[u8]) -> Result<TokenResponseSchema, ()> {
+    if !(200..300).contains(&status_code) {
+        return Err(());
+    }
+
+    let content = std::str::from_utf8(bytes).map_err(|_| ())?;
+
+    miniserde::json::from_str(content).map_err(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host_storage::fake_storage;
+
+    /// A stand-in for the token endpoint, accepting the "password" password
+    /// and the refresh tokens it issued.
+    pub fn token_endpoint(form: &[(&str, &str)]) -> Result<TokenResponseSchema, ()> {
+        let field = |name: &str| {
+            form.iter()
+                .find(|(field_name, _)| *field_name == name)
+                .map(|(_, value)| *value)
+        };
+
+        if field("client_id") != Some("client") || field("client_secret") != Some("secret") {
+            return Err(());
+        }
+
+        let access_token = match (
+            field("grant_type"),
+            field("password"),
+            field("refresh_token"),
+        ) {
+            (Some("password"), Some("password"), _) => "login",
+            (Some("refresh_token"), _, Some("refresh")) => "refreshed",
+            _ => return Err(()),
+        };
+
+        Ok(TokenResponseSchema {
+            access_token: access_token.to_string(),
+            expires_in: 900,
+            refresh_token: "refresh".to_string(),
+            refresh_expires_in: 3600,
+        })
+    }
+
+    fn credentials() -> Credentials {
+        Credentials {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            username: "username".to_string(),
+            password: "password".to_string(),
+        }
+    }
+
+    fn cached_session(access_token: &str, refresh_token: &str, issued_at: u32) -> Option<Session> {
+        Some(Session {
+            client_id: "client".to_string(),
+            username: "username".to_string(),
+            access_token: access_token.to_string(),
+            refresh_token: refresh_token.to_string(),
+            expires_at: issued_at + 900,
+            refresh_expires_at: issued_at + 3600,
+        })
+    }
+
+    #[test]
+    fn test_session_access_token_login() {
+        let mut session = None;
+
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || Some(1000)),
+            Ok("login".to_string())
+        );
+        assert_eq!(session.as_ref().unwrap().expires_at, 1900);
+        assert_eq!(session.as_ref().unwrap().refresh_expires_at, 4600);
+
+        let wrong_credentials = Credentials {
+            password: "wrong".to_string(),
+            ..credentials()
+        };
+        assert_eq!(
+            session_access_token(&mut None, &wrong_credentials, || Some(1000)),
+            Err(())
+        );
+    }
+
+    #[test]
+    fn test_session_access_token_reuse() {
+        let mut session = cached_session("cached", "refresh", 0);
+
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || Some(100)),
+            Ok("cached".to_string())
+        );
+    }
+
+    #[test]
+    fn test_session_access_token_refresh() {
+        // The access token is about to expire
+        let mut session = cached_session("cached", "refresh", 0);
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || Some(900 - EXPIRY_MARGIN)),
+            Ok("refreshed".to_string())
+        );
+
+        // The refresh token was revoked
+        let mut session = cached_session("cached", "revoked", 0);
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || Some(1000)),
+            Ok("login".to_string())
+        );
+
+        // The refresh token expired too
+        let mut session = cached_session("cached", "refresh", 0);
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || Some(4000)),
+            Ok("login".to_string())
+        );
+
+        // The clock is unknown
+        let mut session = cached_session("cached", "refresh", 0);
+        assert_eq!(
+            session_access_token(&mut session, &credentials(), || None),
+            Ok("refreshed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_session_access_token_changed_credentials() {
+        let mut session = cached_session("cached", "refresh", 0);
+        let other_credentials = Credentials {
+            username: "other".to_string(),
+            ..credentials()
+        };
+
+        assert_eq!(
+            session_access_token(&mut session, &other_credentials, || Some(100)),
+            Ok("login".to_string())
+        );
+        assert_eq!(session.unwrap().username, "other");
+    }
+
+    #[test]
+    fn test_encode_form() {
+        assert_eq!(
+            encode_form(&[("grant_type", "password"), ("password", "pässword&=1")]),
+            "grant_type=password&password=p%C3%A4ssword%26%3D1"
+        );
+    }
+
+    #[test]
+    fn test_parse_token_response() {
+        let token_response = br#"{
+            "access_token": "access",
+            "expires_in": 900,
+            "refresh_expires_in": 3600,
+            "refresh_token": "refresh"
+        }"#;
+        let error_response = br#"{
+            "error": "invalid_grant",
+            "error_description": "Invalid user credentials"
+        }"#;
+
+        assert_eq!(
+            parse_token_response(200, token_response),
+            Ok(TokenResponseSchema {
+                access_token: "access".to_string(),
+                expires_in: 900,
+                refresh_token: "refresh".to_string(),
+                refresh_expires_in: 3600,
+            })
+        );
+        assert_eq!(parse_token_response(401, token_response), Err(()));
+        assert_eq!(parse_token_response(401, error_response), Err(()));
+        assert_eq!(parse_token_response(200, b"not json"), Err(()));
+    }
+
+    #[test]
+    fn test_session_storage() {
+        let session = cached_session("cached", "refresh", 0).unwrap();
+
+        // The session is kept until the refresh token expires
+        save_session(&session, Some(1000));
+
+        assert_eq!(fake_storage::ttl_seconds(SESSION_KEY), Some(Some(2600)));
+        assert_eq!(load_session(), Some(session));
+    }
+}
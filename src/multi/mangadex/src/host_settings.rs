@@ -36,6 +36,57 @@ impl HostSettings {
         }
     }
 
+    /// Get the chapter languages of a specific manga, falling back to the
+    /// global languages when the manga has no override.
+    pub fn get_manga_languages(manga_id: &str) -> Vec<String> {
+        let languages = Self::get_language_overrides()
+            .into_iter()
+            .find(|(id, _)| id == manga_id)
+            .map(|(_, languages)| languages);
+
+        match languages {
+            Some(languages) if !languages.is_empty() => languages,
+            _ => Self::get_languages(),
+        }
+    }
+
+    /// Get the chapter languages of any manga: the global languages along with
+    /// the languages of every override.
+    pub fn get_all_languages() -> Vec<String> {
+        let mut languages = Self::get_languages();
+
+        for (_, override_languages) in Self::get_language_overrides() {
+            for language in override_languages {
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+        }
+
+        languages
+    }
+
+    fn get_language_overrides() -> Vec<(String, Vec<String>)> {
+        let overrides = match setting_get!("language_overrides") {
+            Ok(Value::Map(value)) => value,
+            _ => vec![],
+        };
+
+        // The overrides map manga IDs to comma separated languages
+        overrides
+            .into_iter()
+            .map(|(manga_id, languages)| {
+                let languages = languages
+                    .split(',')
+                    .map(|language| language.trim().to_string())
+                    .filter(|language| !language.is_empty())
+                    .collect();
+
+                (manga_id, languages)
+            })
+            .collect()
+    }
+
     pub fn get_cover_quality() -> u64 {
         match setting_get!("cover_quality") {
             Ok(Value::Number(Number::U64(value))) => value,
@@ -43,6 +94,39 @@ impl HostSettings {
         }
     }
 
+    pub fn get_cover_source() -> u64 {
+        match setting_get!("cover_source") {
+            Ok(Value::Number(Number::U64(value))) => value,
+            _ => 0,
+        }
+    }
+
+    pub fn get_hide_external_chapters() -> bool {
+        match setting_get!("hide_external_chapters") {
+            Ok(Value::Bool(value)) => value,
+            _ => false,
+        }
+    }
+
+    pub fn get_hide_unavailable_chapters() -> bool {
+        match setting_get!("hide_unavailable_chapters") {
+            Ok(Value::Bool(value)) => value,
+            _ => false,
+        }
+    }
+
+    pub fn get_content_ratings() -> Vec<String> {
+        match setting_get!("content_ratings") {
+            Ok(Value::Array(value)) => value,
+            _ => vec![
+                String::from("safe"),
+                String::from("suggestive"),
+                String::from("erotica"),
+                String::from("pornographic"),
+            ],
+        }
+    }
+
     pub fn get_blocked_groups() -> Vec<String> {
         match setting_get!("blocked_groups") {
             Ok(Value::Array(value)) => value,
@@ -57,6 +141,34 @@ impl HostSettings {
         }
     }
 
+    pub fn get_chapter_title_format() -> String {
+        match setting_get!("chapter_title_format") {
+            Ok(Value::String(value)) => value,
+            _ => String::from("{title}"),
+        }
+    }
+
+    pub fn get_chapter_date() -> u64 {
+        match setting_get!("chapter_date") {
+            Ok(Value::Number(Number::U64(value))) => value,
+            _ => 0,
+        }
+    }
+
+    pub fn get_chapter_deduplication() -> u64 {
+        match setting_get!("chapter_deduplication") {
+            Ok(Value::Number(Number::U64(value))) => value,
+            _ => 0,
+        }
+    }
+
+    pub fn get_preferred_groups() -> Vec<String> {
+        match setting_get!("preferred_groups") {
+            Ok(Value::Array(value)) => value,
+            _ => vec![],
+        }
+    }
+
     pub fn get_force_port_443() -> bool {
         match setting_get!("force_port_443") {
             Ok(Value::Bool(value)) => value,
@@ -70,4 +182,46 @@ impl HostSettings {
             _ => false,
         }
     }
+
+    pub fn get_embed_page_data() -> bool {
+        match setting_get!("embed_page_data") {
+            Ok(Value::Bool(value)) => value,
+            _ => false,
+        }
+    }
+
+    pub fn get_raw_description() -> bool {
+        match setting_get!("raw_description") {
+            Ok(Value::Bool(value)) => value,
+            _ => false,
+        }
+    }
+
+    pub fn get_client_id() -> String {
+        match setting_get!("client_id") {
+            Ok(Value::String(value)) => value,
+            _ => String::new(),
+        }
+    }
+
+    pub fn get_client_secret() -> String {
+        match setting_get!("client_secret") {
+            Ok(Value::String(value)) => value,
+            _ => String::new(),
+        }
+    }
+
+    pub fn get_username() -> String {
+        match setting_get!("username") {
+            Ok(Value::String(value)) => value,
+            _ => String::new(),
+        }
+    }
+
+    pub fn get_password() -> String {
+        match setting_get!("password") {
+            Ok(Value::String(value)) => value,
+            _ => String::new(),
+        }
+    }
 }
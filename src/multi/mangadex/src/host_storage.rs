@@ -0,0 +1,126 @@
+/// Call a function of the host storage.
+#[doc(hidden)]
+macro_rules! storage_call {
+    ($function:ident($($arg:expr),*)) => {{
+        #[cfg(not(test))]
+        let result = crate::bindings::midoku::storage::storage::$function($($arg),*);
+        #[cfg(test)]
+        let result = fake_storage::$function($($arg),*);
+        result
+    }};
+}
+
+/// The key-value store of the host, kept across instances of the extension.
+pub struct HostStorage;
+
+impl HostStorage {
+    pub fn get(key: &str) -> Option<Vec<u8>> {
+        storage_call!(get(key))
+    }
+
+    /// Set the value of a key, expiring after `ttl_seconds` if set.
+    pub fn set(key: &str, value: &[u8], ttl_seconds: Option<u32>) -> Result<(), ()> {
+        storage_call!(set(key, value, ttl_seconds))
+    }
+
+    pub fn delete(key: &str) -> Result<(), ()> {
+        storage_call!(delete(key))
+    }
+}
+
+/// An in-memory stand-in for the host storage. Its clock only moves through
+/// `advance`, so that the entries expire when the tests decide.
+#[cfg(test)]
+pub(crate) mod fake_storage {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    struct Entry {
+        value: Vec<u8>,
+        ttl_seconds: Option<u32>,
+        expires_at: Option<u32>,
+    }
+
+    static NOW: AtomicU32 = AtomicU32::new(0);
+    static STORAGE: Mutex<BTreeMap<String, Entry>> = Mutex::new(BTreeMap::new());
+
+    pub fn get(key: &str) -> Option<Vec<u8>> {
+        let now = NOW.load(Ordering::Relaxed);
+        let storage = STORAGE.lock().unwrap();
+        let entry = storage.get(key)?;
+
+        match entry.expires_at {
+            Some(expires_at) if now >= expires_at => None,
+            _ => Some(entry.value.clone()),
+        }
+    }
+
+    pub fn set(key: &str, value: &[u8], ttl_seconds: Option<u32>) -> Result<(), ()> {
+        let now = NOW.load(Ordering::Relaxed);
+        let entry = Entry {
+            value: value.to_vec(),
+            ttl_seconds,
+            expires_at: ttl_seconds.map(|ttl_seconds| now.saturating_add(ttl_seconds)),
+        };
+
+        STORAGE.lock().unwrap().insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    pub fn delete(key: &str) -> Result<(), ()> {
+        STORAGE.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    /// Get the TTL a key was last set with.
+    pub fn ttl_seconds(key: &str) -> Option<Option<u32>> {
+        let storage = STORAGE.lock().unwrap();
+        storage.get(key).map(|entry| entry.ttl_seconds)
+    }
+
+    /// Move the clock of the storage forward.
+    pub fn advance(seconds: u32) {
+        NOW.fetch_add(seconds, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_storage() {
+        assert_eq!(HostStorage::get("host_storage"), None);
+
+        HostStorage::set("host_storage", b"value", None).unwrap();
+        assert_eq!(HostStorage::get("host_storage"), Some(b"value".to_vec()));
+
+        HostStorage::set("host_storage", b"other", Some(60)).unwrap();
+        assert_eq!(HostStorage::get("host_storage"), Some(b"other".to_vec()));
+        assert_eq!(fake_storage::ttl_seconds("host_storage"), Some(Some(60)));
+
+        HostStorage::delete("host_storage").unwrap();
+        assert_eq!(HostStorage::get("host_storage"), None);
+        assert_eq!(HostStorage::delete("host_storage"), Ok(()));
+    }
+
+    #[test]
+    fn test_host_storage_ttl() {
+        HostStorage::set("host_storage_ttl", b"value", Some(60)).unwrap();
+        HostStorage::set("host_storage_no_ttl", b"value", None).unwrap();
+
+        fake_storage::advance(59);
+        assert_eq!(
+            HostStorage::get("host_storage_ttl"),
+            Some(b"value".to_vec())
+        );
+
+        fake_storage::advance(1);
+        assert_eq!(HostStorage::get("host_storage_ttl"), None);
+        assert_eq!(
+            HostStorage::get("host_storage_no_ttl"),
+            Some(b"value".to_vec())
+        );
+    }
+}
@@ -1,7 +1,13 @@
+mod auth;
 mod host_settings;
+mod host_storage;
+mod page_cache;
+mod page_origin;
 mod schema;
 mod utils;
 
+use std::collections::BTreeMap;
+
 use miniserde::json as miniserde_json;
 
 #[allow(warnings)]
@@ -9,16 +15,36 @@ mod bindings;
 
 use bindings::exports::midoku::bindings::api::Guest;
 use bindings::exports::midoku::types::chapter::Chapter;
+use bindings::exports::midoku::types::cover::Cover;
+use bindings::exports::midoku::types::custom_list::{CustomList, Visibility};
 use bindings::exports::midoku::types::filter::Filter;
-use bindings::exports::midoku::types::manga::Manga;
+use bindings::exports::midoku::types::manga::{Manga, ReadingStatus};
 use bindings::exports::midoku::types::page::Page;
-use bindings::midoku::http::outgoing_handler::{handle, Method};
+use bindings::exports::midoku::types::volume::Volume;
+use bindings::midoku::http::outgoing_handler::{handle, IncomingResponse, Method};
 use bindings::midoku::limiter::rate_limiter::{block, set_burst, set_period_ms};
 
 use crate::host_settings::HostSettings;
-use crate::schema::chapter::ChapterResponseSchema;
+use crate::page_origin::{PageOrigin, UPLOADS_URL};
+use crate::schema::aggregate::AggregateResponseSchema;
+use crate::schema::chapter::{deduplicate_chapters, ChapterDataSchema, ChapterResponseSchema};
+use crate::schema::cover::{cover_url, select_cover, CoverDataSchema, CoverResponseSchema};
+use crate::schema::custom_list::{
+    visibility_name, CustomListRequestSchema, CustomListResponseSchema,
+    CustomListResponseSingleSchema,
+};
 use crate::schema::manga::{MangaResponseSchema, MangaResponseSingleSchema};
 use crate::schema::page::PageResponseSchema;
+use crate::schema::rating::{RatingRequestSchema, RatingResponseSchema};
+use crate::schema::read::{ReadBatchResponseSchema, ReadRequestSchema, ReadResponseSchema};
+use crate::schema::report::{ReportSchema, REPORT_URL};
+use crate::schema::result::ResultResponseSchema;
+use crate::schema::status::{
+    reading_status_name, StatusRequestSchema, StatusResponseSchema, StatusesResponseSchema,
+};
+use crate::utils::base64::base64_encode;
+use crate::utils::clock;
+use crate::utils::datetime::format_timestamp;
 use crate::utils::url_encode::url_encode;
 
 const API_URL: &str = "https://api.mangadex.org";
@@ -35,9 +61,25 @@ impl Guest for Component {
         Ok(())
     }
 
+    fn is_authenticated() -> bool {
+        matches!(auth::access_token(), Ok(Some(_)))
+    }
+
     fn get_manga_list(filters: Vec<Filter>, page: u32) -> Result<(Vec<Manga>, bool), ()> {
-        // Block until the rate limiter allows the request
-        block();
+        for filter in &filters {
+            if let Filter::Listing(listing) = filter {
+                match listing.option_index {
+                    0 => {}
+                    1 => {
+                        let (updates, has_next) = Self::get_latest_updates(page)?;
+                        let manga_list = updates.into_iter().map(|(manga, _)| manga).collect();
+                        return Ok((manga_list, has_next));
+                    }
+                    2 => return fetch_followed_manga(page),
+                    _ => return Err(()),
+                }
+            }
+        }
 
         let limit = 20;
         let offset = page as isize * limit;
@@ -73,18 +115,11 @@ impl Guest for Component {
                     };
                     url.push_str(&format!("&order[{}]={}", option, order));
                 }
+                Filter::Listing(_) => {}
             }
         }
 
-        let headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
-        let response = handle(Method::Get, &url, Some(&headers), None)?;
-
-        let bytes = response.bytes();
-        let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
-
-        // Parse the JSON response
-        let manga_response: MangaResponseSchema =
-            miniserde_json::from_str(&content).map_err(|_| ())?;
+        let manga_response: MangaResponseSchema = get_json(&url)?;
 
         // Parse the manga data
         let mut manga_list = Vec::new();
@@ -92,15 +127,58 @@ impl Guest for Component {
             manga_list.push(manga_data.try_into()?);
         }
 
+        apply_cover_source(&mut manga_list);
+
         let has_next = (offset + limit) < manga_response.total;
 
         Ok((manga_list, has_next))
     }
 
-    fn get_manga_details(manga_id: String) -> Result<Manga, ()> {
-        // Block until the rate limiter allows the request
-        block();
+    fn get_latest_updates(page: u32) -> Result<(Vec<(Manga, Chapter)>, bool), ()> {
+        let limit = 100;
+        let offset = page as isize * limit;
+
+        let url = format!(
+            "{}/chapter\
+                ?limit={}\
+                &offset={}\
+                &order[readableAt]=desc\
+                {}",
+            API_URL,
+            limit,
+            offset,
+            chapter_feed_query(&HostSettings::get_languages())
+        );
+
+        let chapter_response: ChapterResponseSchema = get_json(&url)?;
+        let has_next = (offset + limit) < chapter_response.total;
+
+        // Keep the latest chapter of each manga
+        let mut latest_chapters: Vec<(String, ChapterDataSchema)> = Vec::new();
+        for chapter_data in chapter_response.data {
+            let manga_id = chapter_data.manga_id().ok_or(())?.to_string();
+            if !latest_chapters.iter().any(|(id, _)| *id == manga_id) {
+                latest_chapters.push((manga_id, chapter_data));
+            }
+        }
+
+        let manga_ids: Vec<String> = latest_chapters.iter().map(|(id, _)| id.clone()).collect();
+        let mut manga_by_id = BTreeMap::new();
+        for manga in fetch_manga(&manga_ids)? {
+            manga_by_id.insert(manga.id.clone(), manga);
+        }
+
+        let mut updates = Vec::with_capacity(latest_chapters.len());
+        for (manga_id, chapter_data) in latest_chapters {
+            if let Some(manga) = manga_by_id.remove(&manga_id) {
+                updates.push((manga, chapter_data.try_into()?));
+            }
+        }
+
+        Ok((updates, has_next))
+    }
 
+    fn get_manga_details(manga_id: String) -> Result<Manga, ()> {
         let url = format!(
             "{}/manga/{}\
                 ?includes[]=cover_art\
@@ -109,115 +187,861 @@ impl Guest for Component {
             API_URL, manga_id,
         );
 
-        let headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
-        let response = handle(Method::Get, &url, Some(&headers), None)?;
+        let manga_response: MangaResponseSingleSchema = get_json(&url)?;
+
+        let mut manga: Manga = manga_response.data.try_into()?;
+        apply_cover_source(std::slice::from_mut(&mut manga));
 
-        let bytes = response.bytes();
-        let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
+        Ok(manga)
+    }
+
+    fn get_manga_details_batch(manga_ids: Vec<String>) -> Result<(Vec<Manga>, Vec<String>), ()> {
+        let mut manga_by_id = BTreeMap::new();
+        for manga in fetch_manga(&manga_ids)? {
+            manga_by_id.insert(manga.id.clone(), manga);
+        }
+
+        // Keep the order of the requested IDs
+        let mut manga_list = Vec::with_capacity(manga_ids.len());
+        let mut missing_ids = Vec::new();
+        for manga_id in manga_ids {
+            match manga_by_id.get(&manga_id) {
+                Some(manga) => manga_list.push(manga.clone()),
+                None => missing_ids.push(manga_id),
+            }
+        }
 
-        // Parse the JSON response
-        let manga_response: MangaResponseSingleSchema =
-            miniserde_json::from_str(&content).map_err(|_| ())?;
+        Ok((manga_list, missing_ids))
+    }
 
-        Ok(manga_response.data.try_into()?)
+    fn get_manga_covers(manga_id: String) -> Result<Vec<Cover>, ()> {
+        let mut cover_list = Vec::new();
+        for cover_data in fetch_covers(&[manga_id], None)? {
+            cover_list.push(cover_data.try_into()?);
+        }
+
+        Ok(cover_list)
     }
 
     fn get_chapter_list(manga_id: String) -> Result<Vec<Chapter>, ()> {
-        // Block until the rate limiter allows the request
-        block();
-
         let limit = 500;
 
-        let mut url = format!(
+        let url = format!(
             "{}/manga/{}/feed\
                 ?limit={}\
                 &order[volume]=asc\
                 &order[chapter]=asc\
-                &contentRating[]=safe\
-                &contentRating[]=suggestive\
-                &contentRating[]=erotica\
-                &contentRating[]=pornographic\
-                &includes[]=user\
-                &includes[]=scanlation_group",
-            API_URL, manga_id, limit
+                {}",
+            API_URL,
+            manga_id,
+            limit,
+            chapter_feed_query(&HostSettings::get_manga_languages(&manga_id))
         );
 
-        for language in HostSettings::get_languages() {
-            url.push_str(&format!("&translatedLanguage[]={}", language));
+        let mut chapter_list = Vec::new();
+        for chapter_data in deduplicate(fetch_chapters(&url, limit)?) {
+            chapter_list.push(chapter_data.try_into()?);
         }
 
-        for group in HostSettings::get_blocked_groups() {
-            url.push_str(&format!("&excludedGroups[]={}", group));
-        }
+        Ok(chapter_list)
+    }
 
-        for uploader in HostSettings::get_blocked_uploaders() {
-            url.push_str(&format!("&excludedUploaders[]={}", uploader));
-        }
+    fn get_chapter_list_since(manga_id: String, since: u32) -> Result<Vec<Chapter>, ()> {
+        let limit = 500;
 
-        let headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
-        let response = handle(Method::Get, &url, Some(&headers), None)?;
+        let url = format!(
+            "{}/manga/{}/feed\
+                ?limit={}\
+                &order[volume]=asc\
+                &order[chapter]=asc\
+                {}",
+            API_URL,
+            manga_id,
+            limit,
+            chapter_feed_query(&HostSettings::get_manga_languages(&manga_id))
+        );
 
-        let bytes = response.bytes();
-        let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
+        let mut chapter_list = Vec::new();
+        for chapter_data in fetch_chapters_since(&url, limit, since)? {
+            chapter_list.push(chapter_data.try_into()?);
+        }
 
-        // Parse the JSON response
-        let chapter_response: ChapterResponseSchema =
-            miniserde_json::from_str(&content).map_err(|_| ())?;
+        Ok(chapter_list)
+    }
 
-        let mut chapter_list = Vec::with_capacity(chapter_response.total as usize);
-        for chapter_data in chapter_response.data {
-            chapter_list.push(chapter_data.try_into()?);
+    fn get_chapter_list_since_batch(
+        manga_ids: Vec<String>,
+        since: u32,
+    ) -> Result<(Vec<(String, Chapter)>, Vec<String>), ()> {
+        // The chapter endpoint only filters by a single manga, so each manga
+        // is fetched through its own feed
+        let mut chapter_list = Vec::new();
+        let mut failed_manga_ids = Vec::new();
+        for manga_id in manga_ids {
+            match Self::get_chapter_list_since(manga_id.clone(), since) {
+                Ok(chapters) => chapter_list.extend(
+                    chapters
+                        .into_iter()
+                        .map(|chapter| (manga_id.clone(), chapter)),
+                ),
+                Err(_) => failed_manga_ids.push(manga_id),
+            }
         }
 
-        let mut offset = limit;
-        while offset < chapter_response.total {
-            offset += limit;
+        Ok((chapter_list, failed_manga_ids))
+    }
 
-            let response = handle(
-                Method::Get,
-                &format!("{}&offset={}", url, offset),
-                Some(&headers),
-                None,
-            )?;
+    fn get_followed_feed(since: u32) -> Result<Vec<(String, Chapter)>, ()> {
+        require_authentication()?;
 
-            let bytes = response.bytes();
-            let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
+        let limit = 500;
+
+        // The feed can only be filtered by a single set of languages, so the
+        // languages of each manga are applied afterwards
+        let url = format!(
+            "{}/user/follows/manga/feed\
+                ?limit={}\
+                &order[updatedAt]=asc\
+                {}",
+            API_URL,
+            limit,
+            chapter_feed_query(&HostSettings::get_all_languages())
+        );
 
-            // Parse the JSON response
-            let chapter_response: ChapterResponseSchema =
-                miniserde_json::from_str(&content).map_err(|_| ())?;
+        let mut languages_by_manga: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut chapter_list = Vec::new();
+        for chapter_data in fetch_chapters_since(&url, limit, since)? {
+            let manga_id = chapter_data.manga_id().ok_or(())?.to_string();
 
-            for chapter_data in chapter_response.data {
-                chapter_list.push(chapter_data.try_into()?);
+            let languages = languages_by_manga
+                .entry(manga_id.clone())
+                .or_insert_with(|| HostSettings::get_manga_languages(&manga_id));
+            if !languages.contains(&chapter_data.attributes.translated_language) {
+                continue;
             }
+
+            chapter_list.push((manga_id, chapter_data.try_into()?));
         }
 
         Ok(chapter_list)
     }
 
-    fn get_page_list(_manga_id: String, chapter_id: String) -> Result<Vec<Page>, ()> {
-        // Block until the rate limiter allows the request
-        block();
+    fn get_volume_structure(manga_id: String) -> Result<Vec<Volume>, ()> {
+        let languages: Vec<String> = HostSettings::get_manga_languages(&manga_id)
+            .into_iter()
+            .map(|language| format!("translatedLanguage[]={}", language))
+            .collect();
 
         let url = format!(
-            "{}/at-home/server/{}\
-                ?forcePort443={}",
+            "{}/manga/{}/aggregate?{}",
             API_URL,
-            chapter_id,
-            HostSettings::get_force_port_443()
+            manga_id,
+            languages.join("&")
         );
 
-        let headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
-        let response = handle(Method::Get, &url, Some(&headers), None)?;
+        let aggregate_response: AggregateResponseSchema = get_json(&url)?;
+
+        aggregate_response.try_into()
+    }
+
+    fn get_page_list(_manga_id: String, chapter_id: String) -> Result<Vec<Page>, ()> {
+        let mut page_list = fetch_page_list(&chapter_id)?;
+
+        // The pages that can't be downloaded are left without data, for the
+        // host to load them itself
+        if HostSettings::get_embed_page_data() {
+            for page in page_list.iter_mut() {
+                let _ = embed_page_data(page);
+            }
+        }
+
+        Ok(page_list)
+    }
+
+    fn get_page_data(_manga_id: String, chapter_id: String, page_index: u32) -> Result<Page, ()> {
+        let mut page = fetch_page_list(&chapter_id)?
+            .into_iter()
+            .find(|page| page.index == page_index)
+            .ok_or(())?;
+
+        embed_page_data(&mut page)?;
+
+        Ok(page)
+    }
+
+    fn get_fallback_page_list(manga_id: String, chapter_id: String) -> Result<Vec<Page>, ()> {
+        // A fresh at-home server is needed as the cached one failed, while the
+        // MangaDex servers serve the same files as any at-home server
+        if page_origin::fallback(&chapter_id) == PageOrigin::AtHome {
+            page_cache::invalidate(&chapter_id);
+        }
+
+        Self::get_page_list(manga_id, chapter_id)
+    }
+
+    fn invalidate_page_list(_manga_id: String, chapter_id: String) -> Result<(), ()> {
+        page_cache::invalidate(&chapter_id);
+
+        Ok(())
+    }
+
+    fn report_page_result(
+        url: String,
+        success: bool,
+        bytes: u32,
+        duration_ms: u32,
+        cached: bool,
+    ) -> Result<(), ()> {
+        let report = ReportSchema {
+            url,
+            success,
+            bytes,
+            duration: duration_ms,
+            cached,
+        };
+
+        if !report.should_report() {
+            return Ok(());
+        }
+
+        // The report endpoint is not rate limited like the API
+        let headers = vec![
+            ("User-Agent".to_string(), HostSettings::get_user_agent()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ];
+        let body = miniserde_json::to_string(&report).into_bytes();
+        handle(Method::Post, REPORT_URL, Some(&headers), Some(&body))?;
+
+        Ok(())
+    }
+
+    fn get_read_chapters(manga_id: String) -> Result<Vec<String>, ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/read", API_URL, manga_id);
+
+        let read_response: ReadResponseSchema = get_json(&url)?;
+
+        Ok(read_response.data)
+    }
+
+    fn get_read_chapters_batch(manga_ids: Vec<String>) -> Result<Vec<(String, Vec<String>)>, ()> {
+        require_authentication()?;
+
+        let limit = 100;
+
+        let mut read_chapters = BTreeMap::new();
+        for manga_ids in manga_ids.chunks(limit) {
+            let mut url = format!("{}/manga/read?grouped=true", API_URL);
+            for manga_id in manga_ids {
+                url.push_str(&format!("&ids[]={}", manga_id));
+            }
+
+            let read_batch_response: ReadBatchResponseSchema = get_json(&url)?;
+            read_chapters.extend(read_batch_response.read_chapters()?);
+        }
+
+        // Keep the order of the input, with the unread manga included
+        let read_chapters = manga_ids
+            .into_iter()
+            .map(|manga_id| {
+                let chapter_ids = read_chapters.get(&manga_id).cloned().unwrap_or_default();
+                (manga_id, chapter_ids)
+            })
+            .collect();
+
+        Ok(read_chapters)
+    }
+
+    fn mark_chapters(
+        manga_id: String,
+        read_chapter_ids: Vec<String>,
+        unread_chapter_ids: Vec<String>,
+    ) -> Result<(), ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/read", API_URL, manga_id);
+        let read_request = ReadRequestSchema {
+            chapter_ids_read: read_chapter_ids,
+            chapter_ids_unread: unread_chapter_ids,
+        };
+
+        send_json(
+            Method::Post,
+            &url,
+            Some(miniserde_json::to_string(&read_request)),
+        )
+    }
+
+    fn mark_chapters_batch(
+        read_markers: Vec<(String, Vec<String>, Vec<String>)>,
+    ) -> Result<Vec<String>, ()> {
+        require_authentication()?;
+
+        let mut failed_manga_ids = Vec::new();
+        for (manga_id, read_chapter_ids, unread_chapter_ids) in read_markers {
+            if Self::mark_chapters(manga_id.clone(), read_chapter_ids, unread_chapter_ids).is_err()
+            {
+                failed_manga_ids.push(manga_id);
+            }
+        }
+
+        Ok(failed_manga_ids)
+    }
+
+    fn get_reading_status(manga_id: String) -> Result<Option<ReadingStatus>, ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/status", API_URL, manga_id);
+
+        let status_response: StatusResponseSchema = get_json(&url)?;
+
+        Ok(status_response.reading_status())
+    }
+
+    fn get_reading_statuses() -> Result<Vec<(String, ReadingStatus)>, ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/status", API_URL);
+
+        let statuses_response: StatusesResponseSchema = get_json(&url)?;
+
+        statuses_response.reading_statuses()
+    }
+
+    fn set_reading_status(
+        manga_id: String,
+        reading_status: Option<ReadingStatus>,
+    ) -> Result<(), ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/status", API_URL, manga_id);
+        let status_request = StatusRequestSchema {
+            status: reading_status.map(|status| reading_status_name(status).to_string()),
+        };
+
+        send_json(
+            Method::Post,
+            &url,
+            Some(miniserde_json::to_string(&status_request)),
+        )
+    }
+
+    fn get_ratings(manga_ids: Vec<String>) -> Result<Vec<(String, u8)>, ()> {
+        require_authentication()?;
+
+        let limit = 100;
+
+        let mut ratings = Vec::new();
+        for manga_ids in manga_ids.chunks(limit) {
+            let url = format!(
+                "{}/rating?{}",
+                API_URL,
+                manga_ids
+                    .iter()
+                    .map(|manga_id| format!("manga[]={}", manga_id))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            );
+
+            let rating_response: RatingResponseSchema = get_json(&url)?;
+            ratings.extend(rating_response.ratings()?);
+        }
+
+        Ok(ratings)
+    }
+
+    fn set_rating(manga_id: String, rating: Option<u8>) -> Result<(), ()> {
+        require_authentication()?;
+
+        let url = format!("{}/rating/{}", API_URL, manga_id);
+
+        match rating {
+            Some(rating @ 1..=10) => {
+                let rating_request = RatingRequestSchema { rating };
+                send_json(
+                    Method::Post,
+                    &url,
+                    Some(miniserde_json::to_string(&rating_request)),
+                )
+            }
+            Some(_) => Err(()),
+            None => send_json(Method::Delete, &url, None),
+        }
+    }
+
+    fn follow_manga(manga_id: String) -> Result<(), ()> {
+        require_authentication()?;
 
-        let bytes = response.bytes();
-        let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
+        let url = format!("{}/manga/{}/follow", API_URL, manga_id);
+
+        send_json(Method::Post, &url, None)
+    }
 
-        // Parse the JSON response
-        let page_response: PageResponseSchema =
-            miniserde_json::from_str(&content).map_err(|_| ())?;
+    fn unfollow_manga(manga_id: String) -> Result<(), ()> {
+        require_authentication()?;
 
-        Ok(page_response.into())
+        let url = format!("{}/manga/{}/follow", API_URL, manga_id);
+
+        send_json(Method::Delete, &url, None)
+    }
+
+    fn follow_manga_batch(manga_ids: Vec<String>) -> Result<Vec<String>, ()> {
+        require_authentication()?;
+
+        // Each follow is a request of its own, paced by the rate limiter
+        let mut failed_manga_ids = Vec::new();
+        for manga_id in manga_ids {
+            if Self::follow_manga(manga_id.clone()).is_err() {
+                failed_manga_ids.push(manga_id);
+            }
+        }
+
+        Ok(failed_manga_ids)
+    }
+
+    fn get_custom_lists() -> Result<Vec<CustomList>, ()> {
+        require_authentication()?;
+
+        let limit = 100;
+
+        let url = format!("{}/user/list?limit={}", API_URL, limit);
+
+        let mut custom_lists = Vec::new();
+        let mut offset = 0;
+        loop {
+            let custom_list_response: CustomListResponseSchema =
+                get_json(&format!("{}&offset={}", url, offset))?;
+            for custom_list_data in custom_list_response.data {
+                custom_lists.push(custom_list_data.try_into()?);
+            }
+
+            offset += limit;
+            if offset >= custom_list_response.total {
+                break;
+            }
+        }
+
+        Ok(custom_lists)
+    }
+
+    fn create_custom_list(
+        name: String,
+        visibility: Visibility,
+        manga_ids: Vec<String>,
+    ) -> Result<CustomList, ()> {
+        require_authentication()?;
+
+        let url = format!("{}/list", API_URL);
+        let custom_list_request = CustomListRequestSchema {
+            name,
+            visibility: visibility_name(visibility).to_string(),
+            manga: manga_ids,
+        };
+
+        let custom_list_response: CustomListResponseSingleSchema = request_json(
+            Method::Post,
+            &url,
+            Some(miniserde_json::to_string(&custom_list_request)),
+        )?;
+
+        custom_list_response.data.try_into()
+    }
+
+    fn add_to_custom_list(manga_id: String, list_id: String) -> Result<(), ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/list/{}", API_URL, manga_id, list_id);
+
+        send_json(Method::Post, &url, None)
+    }
+
+    fn remove_from_custom_list(manga_id: String, list_id: String) -> Result<(), ()> {
+        require_authentication()?;
+
+        let url = format!("{}/manga/{}/list/{}", API_URL, manga_id, list_id);
+
+        send_json(Method::Delete, &url, None)
+    }
+}
+
+/// Send a GET request to the API and parse its JSON response.
+fn get_json<T: miniserde::Deserialize>(url: &str) -> Result<T, ()> {
+    request_json(Method::Get, url, None)
+}
+
+/// Send a request to the API that returns no data, with an optional JSON
+/// body.
+fn send_json(method: Method, url: &str, body: Option<String>) -> Result<(), ()> {
+    let result_response: ResultResponseSchema = request_json(method, url, body)?;
+
+    match result_response.is_ok() {
+        true => Ok(()),
+        false => Err(()),
+    }
+}
+
+/// Send a request to the API with an optional JSON body and parse its JSON
+/// response.
+fn request_json<T: miniserde::Deserialize>(
+    method: Method,
+    url: &str,
+    body: Option<String>,
+) -> Result<T, ()> {
+    let body = body.map(String::into_bytes);
+
+    // The requests that need an account fail early if it can't be logged in,
+    // the others are sent without a token
+    let access_token = auth::optional_access_token();
+    let mut response = send_request(method, url, access_token.as_deref(), body.as_deref())?;
+
+    // The access token may have been revoked, or have expired while the clock
+    // stood still. Renew it and try again once.
+    if response.status_code() == 401 && access_token.is_some() {
+        auth::expire_access_token();
+
+        let access_token = auth::optional_access_token();
+        response = send_request(method, url, access_token.as_deref(), body.as_deref())?;
+    }
+
+    if !(200..300).contains(&response.status_code()) {
+        return Err(());
+    }
+
+    let bytes = response.bytes();
+    let content = std::str::from_utf8(&bytes).map_err(|_| ())?;
+
+    // Parse the JSON response
+    miniserde_json::from_str(content).map_err(|_| ())
+}
+
+/// Send a request to the API, authenticated with `access_token` if set.
+fn send_request(
+    method: Method,
+    url: &str,
+    access_token: Option<&str>,
+    body: Option<&[u8]>,
+) -> Result<IncomingResponse, ()> {
+    // Block until the rate limiter allows the request
+    block();
+
+    let mut headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
+    if let Some(access_token) = access_token {
+        headers.push((
+            "Authorization".to_string(),
+            format!("Bearer {}", access_token),
+        ));
+    }
+    if body.is_some() {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+
+    let response = handle(method, url, Some(&headers), body)?;
+    clock::observe_date_header(&response.headers());
+
+    Ok(response)
+}
+
+/// Get the pages of a chapter, without their data.
+fn fetch_page_list(chapter_id: &str) -> Result<Vec<Page>, ()> {
+    let data_saver = HostSettings::get_data_saver();
+
+    // Reuse the at-home server of the chapter while its URLs are valid
+    let cached_page_response =
+        clock::now().and_then(|now| page_cache::get(chapter_id, data_saver, now));
+    let mut page_response = match cached_page_response {
+        Some(page_response) => page_response,
+        None => fetch_page_response(chapter_id, data_saver)?,
+    };
+
+    if page_origin::get(chapter_id) == PageOrigin::Uploads {
+        page_response.base_url = UPLOADS_URL.to_string();
+    }
+
+    Ok(page_response.into())
+}
+
+/// Fail early if no account is configured or it can't be logged in, for the
+/// features that need one.
+fn require_authentication() -> Result<(), ()> {
+    match auth::access_token()? {
+        Some(_) => Ok(()),
+        None => Err(()),
+    }
+}
+
+/// Fetch a page of the manga followed by the logged in account.
+fn fetch_followed_manga(page: u32) -> Result<(Vec<Manga>, bool), ()> {
+    require_authentication()?;
+
+    let limit = 20;
+    let offset = page as isize * limit;
+
+    let url = format!(
+        "{}/user/follows/manga\
+            ?includes[]=cover_art\
+            &includes[]=author\
+            &includes[]=artist\
+            &limit={}\
+            &offset={}",
+        API_URL, limit, offset
+    );
+
+    let manga_response: MangaResponseSchema = get_json(&url)?;
+
+    // Parse the manga data
+    let mut manga_list = Vec::new();
+    for manga_data in manga_response.data {
+        manga_list.push(manga_data.try_into()?);
+    }
+
+    apply_cover_source(&mut manga_list);
+
+    let has_next = (offset + limit) < manga_response.total;
+
+    Ok((manga_list, has_next))
+}
+
+/// Fetch the at-home server of a chapter and cache it.
+fn fetch_page_response(chapter_id: &str, data_saver: bool) -> Result<PageResponseSchema, ()> {
+    let url = format!(
+        "{}/at-home/server/{}\
+            ?forcePort443={}",
+        API_URL,
+        chapter_id,
+        HostSettings::get_force_port_443()
+    );
+
+    let page_response: PageResponseSchema = get_json(&url)?;
+
+    if let Some(now) = clock::now() {
+        page_cache::insert(chapter_id, data_saver, page_response.clone(), now);
+    }
+
+    Ok(page_response)
+}
+
+/// Download the image of a page and fill its base64-encoded data.
+///
+/// The download is not reported to MangaDex@Home, as it can't be timed.
+fn embed_page_data(page: &mut Page) -> Result<(), ()> {
+    // Image downloads share the rate limiter with the API requests, the host
+    // has a single one
+    block();
+
+    let response = handle(Method::Get, &page.url, page.headers.as_deref(), None)?;
+    clock::observe_date_header(&response.headers());
+
+    if !(200..300).contains(&response.status_code()) {
+        return Err(());
+    }
+
+    page.base64 = base64_encode(&response.bytes()).into_bytes();
+
+    Ok(())
+}
+
+/// Fetch the details of the given manga, 100 at a time.
+///
+/// Manga that could not be found or parsed are left out of the returned
+/// list.
+fn fetch_manga(manga_ids: &[String]) -> Result<Vec<Manga>, ()> {
+    let limit = 100;
+
+    let mut manga_list = Vec::with_capacity(manga_ids.len());
+    for manga_ids in manga_ids.chunks(limit) {
+        let mut url = format!(
+            "{}/manga\
+                ?includes[]=cover_art\
+                &includes[]=author\
+                &includes[]=artist\
+                &contentRating[]=safe\
+                &contentRating[]=suggestive\
+                &contentRating[]=erotica\
+                &contentRating[]=pornographic\
+                &limit={}",
+            API_URL, limit
+        );
+
+        for manga_id in manga_ids {
+            url.push_str(&format!("&ids[]={}", manga_id));
+        }
+
+        let manga_response: MangaResponseSchema = get_json(&url)?;
+
+        // The cover source can only be looked up for 100 manga at a time too
+        let chunk_start = manga_list.len();
+        manga_list.extend(
+            manga_response
+                .data
+                .into_iter()
+                .filter_map(|manga_data| manga_data.try_into().ok()),
+        );
+        apply_cover_source(&mut manga_list[chunk_start..]);
+    }
+
+    Ok(manga_list)
+}
+
+/// Fetch every page of a chapter feed.
+///
+/// `url` must not contain an offset, it is added for each page.
+fn fetch_chapters(url: &str, limit: isize) -> Result<Vec<ChapterDataSchema>, ()> {
+    let chapter_response: ChapterResponseSchema = get_json(url)?;
+
+    let mut chapter_list = Vec::with_capacity(chapter_response.total as usize);
+    chapter_list.extend(chapter_response.data);
+
+    let mut offset = limit;
+    while offset < chapter_response.total {
+        let chapter_response: ChapterResponseSchema =
+            get_json(&format!("{}&offset={}", url, offset))?;
+        chapter_list.extend(chapter_response.data);
+
+        offset += limit;
+    }
+
+    Ok(chapter_list)
+}
+
+/// Fetch the chapters of a chapter feed that were added or changed since a
+/// given date.
+///
+/// New chapters and re-uploads are found through their update date, while
+/// delayed releases are found through their publication date. The feed is
+/// fetched once for each, so this costs at least two requests.
+fn fetch_chapters_since(url: &str, limit: isize, since: u32) -> Result<Vec<ChapterDataSchema>, ()> {
+    let since = format_timestamp(since);
+
+    let mut chapter_list = fetch_chapters(&format!("{}&updatedAtSince={}", url, since), limit)?;
+    for chapter_data in fetch_chapters(&format!("{}&publishAtSince={}", url, since), limit)? {
+        if !chapter_list
+            .iter()
+            .any(|chapter| chapter.id == chapter_data.id)
+        {
+            chapter_list.push(chapter_data);
+        }
+    }
+
+    Ok(chapter_list)
+}
+
+/// Remove the duplicate chapters according to the deduplication settings.
+///
+/// Only whole chapter lists are deduplicated, as the uploads the host already
+/// has can't be compared against the chapters changed since a given date.
+fn deduplicate(chapter_list: Vec<ChapterDataSchema>) -> Vec<ChapterDataSchema> {
+    deduplicate_chapters(
+        chapter_list,
+        HostSettings::get_chapter_deduplication(),
+        &HostSettings::get_preferred_groups(),
+    )
+}
+
+/// Get the query parameters shared by the chapter feeds.
+fn chapter_feed_query(languages: &[String]) -> String {
+    let mut query = String::from("&includes[]=user&includes[]=scanlation_group");
+
+    for content_rating in HostSettings::get_content_ratings() {
+        query.push_str(&format!("&contentRating[]={}", content_rating));
+    }
+
+    query.push_str(&format!(
+        "&includeExternalUrl={}&includeUnavailable={}",
+        !HostSettings::get_hide_external_chapters() as u8,
+        !HostSettings::get_hide_unavailable_chapters() as u8
+    ));
+
+    for language in languages {
+        query.push_str(&format!("&translatedLanguage[]={}", language));
+    }
+
+    for group in HostSettings::get_blocked_groups() {
+        query.push_str(&format!("&excludedGroups[]={}", group));
+    }
+
+    for uploader in HostSettings::get_blocked_uploaders() {
+        query.push_str(&format!("&excludedUploaders[]={}", uploader));
+    }
+
+    query
+}
+
+/// Fetch the covers of every volume of the given manga, only those in the
+/// given locale if set.
+fn fetch_covers(manga_ids: &[String], locale: Option<&str>) -> Result<Vec<CoverDataSchema>, ()> {
+    let limit = 100;
+
+    let mut url = format!(
+        "{}/cover\
+            ?limit={}\
+            &order[volume]=asc",
+        API_URL, limit
+    );
+
+    for manga_id in manga_ids {
+        url.push_str(&format!("&manga[]={}", manga_id));
+    }
+
+    if let Some(locale) = locale {
+        url.push_str(&format!("&locales[]={}", locale));
+    }
+
+    let mut cover_list = Vec::new();
+    let mut offset = 0;
+    loop {
+        let cover_response: CoverResponseSchema = get_json(&format!("{}&offset={}", url, offset))?;
+        cover_list.extend(cover_response.data);
+
+        offset += limit;
+        if offset >= cover_response.total {
+            break;
+        }
+    }
+
+    Ok(cover_list)
+}
+
+/// Replace the main cover of the manga with the one of the configured cover
+/// source. The main cover is kept when the covers can't be fetched.
+///
+/// The covers of up to 100 manga are fetched at once, and the cover of each
+/// manga is selected from them by its volume number.
+fn apply_cover_source(manga_list: &mut [Manga]) {
+    let cover_source = HostSettings::get_cover_source();
+    if cover_source == 0 || manga_list.is_empty() {
+        return;
+    }
+
+    // Only the covers in the user's locale are needed for the locale source
+    let locale = HostSettings::get_locale();
+    let cover_locale = (cover_source == 3).then_some(locale.as_str());
+
+    let mut covers = Vec::new();
+    for manga_chunk in manga_list.chunks(100) {
+        let manga_ids: Vec<String> = manga_chunk.iter().map(|manga| manga.id.clone()).collect();
+        if let Ok(chunk_covers) = fetch_covers(&manga_ids, cover_locale) {
+            covers.extend(chunk_covers);
+        }
+    }
+
+    // Group the covers by manga
+    let mut covers_by_manga: BTreeMap<String, Vec<CoverDataSchema>> = BTreeMap::new();
+    for cover in covers {
+        if let Some(manga_id) = cover.manga_id() {
+            let manga_id = manga_id.to_string();
+            covers_by_manga.entry(manga_id).or_default().push(cover);
+        }
+    }
+
+    for manga in manga_list {
+        let Some(covers) = covers_by_manga.get(&manga.id) else {
+            continue;
+        };
+
+        if let Some(cover) = select_cover(covers, cover_source, &locale) {
+            manga.cover_url = cover_url(&manga.id, &cover.attributes.file_name);
+        }
     }
 }
 
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::schema::page::PageResponseSchema;
+
+/// The at-home server URLs are valid for about 15 minutes. The pages are
+/// cached for less than that so that they don't expire while being read.
+const PAGE_CACHE_TTL: u32 = 10 * 60;
+
+struct CachedPages {
+    expires_at: u32,
+    page_response: PageResponseSchema,
+}
+
+/// The at-home server responses, keyed by chapter ID and data saver mode.
+static PAGE_CACHE: Mutex<BTreeMap<(String, bool), CachedPages>> = Mutex::new(BTreeMap::new());
+
+/// Get the cached at-home server response of a chapter, if it has not
+/// expired yet.
+///
+/// The clock stands still between requests, so an entry may still be served
+/// after an idle period. The host invalidates it once its URLs fail.
+pub fn get(chapter_id: &str, data_saver: bool, now: u32) -> Option<PageResponseSchema> {
+    let mut page_cache = PAGE_CACHE.lock().ok()?;
+    let key = (chapter_id.to_string(), data_saver);
+
+    match page_cache.get(&key) {
+        Some(cached_pages) if now < cached_pages.expires_at => {
+            Some(cached_pages.page_response.clone())
+        }
+        Some(_) => {
+            page_cache.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Cache the at-home server response of a chapter fetched at `now`.
+pub fn insert(chapter_id: &str, data_saver: bool, page_response: PageResponseSchema, now: u32) {
+    let Ok(mut page_cache) = PAGE_CACHE.lock() else {
+        return;
+    };
+
+    // Drop the expired entries so that the cache does not grow forever
+    page_cache.retain(|_, cached_pages| now < cached_pages.expires_at);
+
+    page_cache.insert(
+        (chapter_id.to_string(), data_saver),
+        CachedPages {
+            expires_at: now.saturating_add(PAGE_CACHE_TTL),
+            page_response,
+        },
+    );
+}
+
+/// Remove the cached at-home server responses of a chapter.
+pub fn invalidate(chapter_id: &str) {
+    if let Ok(mut page_cache) = PAGE_CACHE.lock() {
+        page_cache.retain(|(id, _), _| id != chapter_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::page::PageChapterSchema;
+
+    fn page_response_schema(hash: &str) -> PageResponseSchema {
+        PageResponseSchema {
+            base_url: "https://example.com".to_string(),
+            chapter: PageChapterSchema {
+                hash: hash.to_string(),
+                data: vec!["1.jpg".to_string()],
+                data_saver: vec!["1s.jpg".to_string()],
+            },
+        }
+    }
+
+    #[test]
+    fn test_page_cache_expiry() {
+        insert("expiry", false, page_response_schema("hash"), 1000);
+
+        assert_eq!(
+            get("expiry", false, 1000),
+            Some(page_response_schema("hash"))
+        );
+        assert_eq!(get("expiry", true, 1000), None);
+        assert_eq!(
+            get("expiry", false, 1000 + PAGE_CACHE_TTL - 1),
+            Some(page_response_schema("hash"))
+        );
+        assert_eq!(get("expiry", false, 1000 + PAGE_CACHE_TTL), None);
+    }
+
+    #[test]
+    fn test_page_cache_invalidate() {
+        insert("invalidate", false, page_response_schema("hash"), 1000);
+        insert("invalidate", true, page_response_schema("hash"), 1000);
+
+        invalidate("invalidate");
+
+        assert_eq!(get("invalidate", false, 1000), None);
+        assert_eq!(get("invalidate", true, 1000), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// The MangaDex servers, used when the at-home nodes keep failing.
+pub const UPLOADS_URL: &str = "https://uploads.mangadex.org";
+
+/// Where the pages of a chapter are loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageOrigin {
+    /// The at-home node assigned by the API.
+    AtHome,
+    /// The MangaDex servers.
+    Uploads,
+}
+
+/// The number of times a fallback was requested for each chapter.
+static FALLBACK_COUNTS: Mutex<BTreeMap<String, u32>> = Mutex::new(BTreeMap::new());
+
+/// Get the origin the pages of a chapter are loaded from.
+pub fn get(chapter_id: &str) -> PageOrigin {
+    let fallback_count = FALLBACK_COUNTS
+        .lock()
+        .ok()
+        .and_then(|fallback_counts| fallback_counts.get(chapter_id).copied())
+        .unwrap_or_default();
+
+    origin_from_fallback_count(fallback_count)
+}
+
+/// Record that the pages of a chapter failed to load, and get the origin to
+/// load them from next.
+///
+/// The first fallback asks for another at-home node, the next ones use the
+/// MangaDex servers for the rest of the session.
+pub fn fallback(chapter_id: &str) -> PageOrigin {
+    let Ok(mut fallback_counts) = FALLBACK_COUNTS.lock() else {
+        return PageOrigin::Uploads;
+    };
+
+    let fallback_count = fallback_counts.entry(chapter_id.to_string()).or_default();
+    *fallback_count = fallback_count.saturating_add(1);
+
+    origin_from_fallback_count(*fallback_count)
+}
+
+fn origin_from_fallback_count(fallback_count: u32) -> PageOrigin {
+    if fallback_count < 2 {
+        PageOrigin::AtHome
+    } else {
+        PageOrigin::Uploads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_origin_fallback() {
+        assert_eq!(get("fallback"), PageOrigin::AtHome);
+
+        assert_eq!(fallback("fallback"), PageOrigin::AtHome);
+        assert_eq!(get("fallback"), PageOrigin::AtHome);
+
+        assert_eq!(fallback("fallback"), PageOrigin::Uploads);
+        assert_eq!(get("fallback"), PageOrigin::Uploads);
+
+        assert_eq!(fallback("fallback"), PageOrigin::Uploads);
+        assert_eq!(get("other"), PageOrigin::AtHome);
+    }
+}
@@ -0,0 +1,203 @@
+use miniserde::{json, Deserialize};
+
+use crate::bindings::exports::midoku::types::volume::{Volume, VolumeChapter};
+use crate::utils::chapter_number::ChapterNumber;
+
+#[derive(Debug, Deserialize)]
+pub struct AggregateResponseSchema {
+    // The volumes are an object keyed by volume, but the API returns an array
+    // instead when the keys are sequential numbers starting at 0 or when
+    // there are no volumes at all. The same goes for the chapters of each
+    // volume, so they are parsed by hand.
+    pub volumes: json::Value,
+}
+
+/// Get the values of an object, or the items of an array.
+fn values(value: &json::Value) -> Result<Vec<&json::Value>, ()> {
+    match value {
+        json::Value::Object(object) => Ok(object.values().collect()),
+        json::Value::Array(array) => Ok(array.iter().collect()),
+        _ => Err(()),
+    }
+}
+
+fn get_string(value: &json::Value, key: &str) -> Result<String, ()> {
+    match value {
+        json::Value::Object(object) => match object.get(key) {
+            Some(json::Value::String(value)) => Ok(value.clone()),
+            _ => Err(()),
+        },
+        _ => Err(()),
+    }
+}
+
+/// Numbers are sorted in ascending order, with the missing ones at the end.
+fn sort_key(number: f32) -> f32 {
+    if number < 0.0 {
+        f32::MAX
+    } else {
+        number
+    }
+}
+
+impl TryInto<Vec<Volume>> for AggregateResponseSchema {
+    type Error = ();
+
+    fn try_into(self) -> Result<Vec<Volume>, Self::Error> {
+        let mut volume_list = Vec::new();
+
+        for volume_value in values(&self.volumes)? {
+            // Chapters without volume are grouped in a "none" volume
+            let volume = match get_string(volume_value, "volume")?.as_str() {
+                "none" => String::new(),
+                volume => volume.to_string(),
+            };
+
+            let chapters = match volume_value {
+                json::Value::Object(object) => object.get("chapters").ok_or(())?,
+                _ => return Err(()),
+            };
+
+            let mut chapter_list = Vec::new();
+            for chapter_value in values(chapters)? {
+                let chapter = match get_string(chapter_value, "chapter")?.as_str() {
+                    "none" => String::new(),
+                    chapter => chapter.to_string(),
+                };
+
+                let mut others = Vec::new();
+                if let json::Value::Object(object) = chapter_value {
+                    if let Some(json::Value::Array(array)) = object.get("others") {
+                        for other in array.iter() {
+                            if let json::Value::String(other) = other {
+                                others.push(other.clone());
+                            }
+                        }
+                    }
+                }
+
+                chapter_list.push(VolumeChapter {
+                    number: ChapterNumber::parse(&chapter).value,
+                    chapter,
+                    id: get_string(chapter_value, "id")?,
+                    count: others.len() as u32 + 1,
+                    others,
+                });
+            }
+
+            chapter_list.sort_by(|a, b| sort_key(a.number).total_cmp(&sort_key(b.number)));
+
+            volume_list.push(Volume {
+                number: ChapterNumber::parse(&volume).value,
+                volume,
+                count: chapter_list.iter().map(|chapter| chapter.count).sum(),
+                chapters: chapter_list,
+            });
+        }
+
+        volume_list.sort_by(|a, b| sort_key(a.number).total_cmp(&sort_key(b.number)));
+
+        Ok(volume_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_response_schema_try_into() {
+        let aggregate_response_schema = r#"{
+            "result": "ok",
+            "volumes": {
+                "none": {
+                    "volume": "none",
+                    "count": 1,
+                    "chapters": {
+                        "10": {
+                            "chapter": "10",
+                            "id": "id-10",
+                            "others": [],
+                            "count": 1
+                        }
+                    }
+                },
+                "1": {
+                    "volume": "1",
+                    "count": 3,
+                    "chapters": {
+                        "2": {
+                            "chapter": "2",
+                            "id": "id-2",
+                            "others": ["id-2-other"],
+                            "count": 2
+                        },
+                        "1": {
+                            "chapter": "1",
+                            "id": "id-1",
+                            "others": [],
+                            "count": 1
+                        }
+                    }
+                }
+            }
+        }"#;
+
+        let aggregate_response_schema: AggregateResponseSchema =
+            miniserde::json::from_str(aggregate_response_schema).unwrap();
+        let volume_list: Vec<Volume> = aggregate_response_schema.try_into().unwrap();
+
+        assert_eq!(volume_list.len(), 2);
+
+        assert_eq!(volume_list[0].volume, "1");
+        assert_eq!(volume_list[0].number, 1.0);
+        assert_eq!(volume_list[0].count, 3);
+        assert_eq!(volume_list[0].chapters.len(), 2);
+        assert_eq!(volume_list[0].chapters[0].chapter, "1");
+        assert_eq!(volume_list[0].chapters[0].id, "id-1");
+        assert_eq!(volume_list[0].chapters[1].chapter, "2");
+        assert_eq!(volume_list[0].chapters[1].number, 2.0);
+        assert_eq!(volume_list[0].chapters[1].others, vec!["id-2-other"]);
+        assert_eq!(volume_list[0].chapters[1].count, 2);
+
+        assert_eq!(volume_list[1].volume, "");
+        assert_eq!(volume_list[1].number, -1.0);
+        assert_eq!(volume_list[1].count, 1);
+        assert_eq!(volume_list[1].chapters[0].chapter, "10");
+    }
+
+    #[test]
+    fn test_aggregate_response_schema_try_into_arrays() {
+        let aggregate_response_schema = r#"{
+            "result": "ok",
+            "volumes": [
+                {
+                    "volume": "0",
+                    "count": 1,
+                    "chapters": [
+                        {
+                            "chapter": "0",
+                            "id": "id-0",
+                            "others": [],
+                            "count": 1
+                        }
+                    ]
+                }
+            ]
+        }"#;
+
+        let aggregate_response_schema: AggregateResponseSchema =
+            miniserde::json::from_str(aggregate_response_schema).unwrap();
+        let volume_list: Vec<Volume> = aggregate_response_schema.try_into().unwrap();
+
+        assert_eq!(volume_list.len(), 1);
+        assert_eq!(volume_list[0].number, 0.0);
+        assert_eq!(volume_list[0].chapters[0].id, "id-0");
+
+        let aggregate_response_schema: AggregateResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "ok", "volumes": [] }"#).unwrap();
+        let volume_list: Vec<Volume> = aggregate_response_schema.try_into().unwrap();
+
+        assert!(volume_list.is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use miniserde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct TokenResponseSchema {
+    pub access_token: String,
+    /// The lifetime of the access token in seconds.
+    pub expires_in: u32,
+    pub refresh_token: String,
+    /// The lifetime of the refresh token in seconds.
+    pub refresh_expires_in: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_response_schema_deserialize() {
+        let token_response_schema = r#"{
+            "access_token": "access",
+            "expires_in": 900,
+            "refresh_expires_in": 2592000,
+            "refresh_token": "refresh",
+            "token_type": "Bearer",
+            "not-before-policy": 0,
+            "session_state": "session",
+            "scope": "email profile"
+        }"#;
+
+        let token_response_schema: TokenResponseSchema =
+            miniserde::json::from_str(token_response_schema).unwrap();
+
+        let expected = TokenResponseSchema {
+            access_token: "access".to_string(),
+            expires_in: 900,
+            refresh_token: "refresh".to_string(),
+            refresh_expires_in: 2592000,
+        };
+
+        assert_eq!(token_response_schema, expected);
+    }
+}
@@ -1,7 +1,14 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
 use miniserde::Deserialize;
 use speedate::DateTime;
 
 use crate::bindings::exports::midoku::types::chapter::Chapter;
+use crate::host_settings::HostSettings;
+use crate::utils::chapter_number::ChapterNumber;
+use crate::utils::chapter_title::format_chapter_title;
+use crate::utils::datetime::clamp_timestamp;
 use crate::HOME_URL;
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -28,6 +35,17 @@ pub struct ChapterAttributesSchema {
     pub translated_language: String,
     #[serde(rename = "publishAt")]
     pub publish_at: String,
+    #[serde(rename = "readableAt")]
+    pub readable_at: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<String>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<String>,
+    #[serde(rename = "externalUrl")]
+    pub external_url: Option<String>,
+    pub pages: Option<isize>,
+    #[serde(rename = "isUnavailable")]
+    pub is_unavailable: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -47,6 +65,105 @@ pub struct ChapterRelationshipAttributesSchema {
     pub username: Option<String>,
 }
 
+impl ChapterDataSchema {
+    /// Get the ID of the manga the chapter belongs to.
+    pub fn manga_id(&self) -> Option<&str> {
+        self.relationships
+            .iter()
+            .find(|relationship| relationship.relationship_type == "manga")
+            .map(|relationship| relationship.id.as_str())
+    }
+
+    /// Get the rank of the chapter's best scanlation group in the preferred
+    /// groups. Chapters without any preferred group are ranked last.
+    fn preferred_group_rank(&self, preferred_groups: &[String]) -> usize {
+        self.relationships
+            .iter()
+            .filter(|relationship| relationship.relationship_type == "scanlation_group")
+            .filter_map(|relationship| {
+                preferred_groups
+                    .iter()
+                    .position(|group| *group == relationship.id)
+            })
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Get the date the chapter was uploaded, falling back to its
+    /// publication date.
+    fn upload_timestamp(&self) -> i64 {
+        let uploaded_at = self
+            .attributes
+            .created_at
+            .as_deref()
+            .unwrap_or(&self.attributes.publish_at);
+
+        DateTime::parse_str_rfc3339(uploaded_at)
+            .map(|date| date.timestamp())
+            .unwrap_or_default()
+    }
+}
+
+/// Keep a single chapter per manga, volume, chapter number and language.
+///
+/// The chapter of the best ranked preferred group is kept. Ties are broken by
+/// the upload date according to `mode`: `1` keeps the latest upload and `2`
+/// keeps the earliest one. Any other mode disables the deduplication. The
+/// order of the chapters is preserved.
+pub fn deduplicate_chapters(
+    chapters: Vec<ChapterDataSchema>,
+    mode: u64,
+    preferred_groups: &[String],
+) -> Vec<ChapterDataSchema> {
+    if !matches!(mode, 1 | 2) {
+        return chapters;
+    }
+
+    let mut deduplicated: Vec<ChapterDataSchema> = Vec::with_capacity(chapters.len());
+    let mut indices: BTreeMap<(Option<String>, Option<String>, String, String), usize> =
+        BTreeMap::new();
+
+    for chapter in chapters {
+        // Oneshots and extras without a chapter number are never duplicates
+        let Some(chapter_number) = chapter.attributes.chapter.clone() else {
+            deduplicated.push(chapter);
+            continue;
+        };
+
+        let key = (
+            chapter.manga_id().map(|manga_id| manga_id.to_string()),
+            chapter.attributes.volume.clone(),
+            chapter_number,
+            chapter.attributes.translated_language.clone(),
+        );
+
+        let Some(&index) = indices.get(&key) else {
+            indices.insert(key, deduplicated.len());
+            deduplicated.push(chapter);
+            continue;
+        };
+
+        let kept = &deduplicated[index];
+        let rank = chapter.preferred_group_rank(preferred_groups);
+        let kept_rank = kept.preferred_group_rank(preferred_groups);
+
+        let replace = match rank.cmp(&kept_rank) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => match mode {
+                1 => chapter.upload_timestamp() > kept.upload_timestamp(),
+                _ => chapter.upload_timestamp() < kept.upload_timestamp(),
+            },
+        };
+
+        if replace {
+            deduplicated[index] = chapter;
+        }
+    }
+
+    deduplicated
+}
+
 impl TryInto<Chapter> for ChapterDataSchema {
     type Error = ();
 
@@ -54,26 +171,44 @@ impl TryInto<Chapter> for ChapterDataSchema {
         let id = self.id;
         let url = format!("{}/chapter/{}", HOME_URL, &id);
         let title = self.attributes.title.unwrap_or_default();
-        let volume = self.attributes.volume.unwrap_or_default();
-        let chapter = self.attributes.chapter.unwrap_or_default();
-        let date_updated = self.attributes.publish_at;
+        let volume = ChapterNumber::parse(&self.attributes.volume.unwrap_or_default());
+        let chapter = ChapterNumber::parse(&self.attributes.chapter.unwrap_or_default());
+        let date_updated = match HostSettings::get_chapter_date() {
+            1 => self.attributes.readable_at,
+            2 => self.attributes.created_at,
+            3 => self.attributes.updated_at,
+            _ => None,
+        }
+        .unwrap_or(self.attributes.publish_at);
         let language = self.attributes.translated_language;
+        let external_url = self.attributes.external_url.unwrap_or_default();
+
+        // Chapters removed from the source (e.g. DMCA takedowns) have no pages
+        let available = !self.attributes.is_unavailable.unwrap_or(false)
+            && (!external_url.is_empty() || self.attributes.pages.unwrap_or(0) > 0);
 
         // When the volume, chapter, and title are empty, it's a oneshot
-        let title = if volume.is_empty() && chapter.is_empty() && title.is_empty() {
+        let title = if volume.display.is_empty() && chapter.display.is_empty() && title.is_empty() {
             "Oneshot".to_string()
         } else {
-            title
+            format_chapter_title(
+                &HostSettings::get_chapter_title_format(),
+                &volume.display,
+                &chapter.display,
+                &title,
+            )
         };
 
-        // Convert the volume and chapter to f32
-        // If the conversion fails (e.g. the manga is a oneshot), set the value to -1.0
-        let volume: f32 = volume.parse().unwrap_or(-1.0);
-        let chapter: f32 = chapter.parse().unwrap_or(-1.0);
+        // The volume and chapter are set to -1.0 when they have no number
+        // (e.g. the manga is a oneshot)
+        let volume = volume.value;
+        let chapter = chapter.value;
 
-        let date_updated = DateTime::parse_str_rfc3339(&date_updated)
-            .map_err(|_| ())?
-            .timestamp() as u32;
+        let date_updated = clamp_timestamp(
+            DateTime::parse_str_rfc3339(&date_updated)
+                .map_err(|_| ())?
+                .timestamp(),
+        );
 
         let mut scanlation_groups = Vec::new();
         let mut uploader = String::new();
@@ -107,6 +242,8 @@ impl TryInto<Chapter> for ChapterDataSchema {
             scanlator,
             url,
             language,
+            external_url,
+            available,
         })
     }
 }
@@ -115,6 +252,42 @@ impl TryInto<Chapter> for ChapterDataSchema {
 mod tests {
     use super::*;
 
+    fn chapter_data_schema(
+        id: &str,
+        chapter: &str,
+        group: &str,
+        publish_at: &str,
+    ) -> ChapterDataSchema {
+        ChapterDataSchema {
+            id: id.to_string(),
+            attributes: ChapterAttributesSchema {
+                title: None,
+                chapter: Some(chapter.to_string()),
+                volume: Some("1".to_string()),
+                translated_language: "en".to_string(),
+                publish_at: publish_at.to_string(),
+                readable_at: None,
+                created_at: None,
+                updated_at: None,
+                external_url: None,
+                pages: Some(20),
+                is_unavailable: Some(false),
+            },
+            relationships: vec![
+                ChapterRelationshipSchema {
+                    id: "manga".to_string(),
+                    relationship_type: "manga".to_string(),
+                    attributes: None,
+                },
+                ChapterRelationshipSchema {
+                    id: group.to_string(),
+                    relationship_type: "scanlation_group".to_string(),
+                    attributes: None,
+                },
+            ],
+        }
+    }
+
     #[test]
     fn test_chapter_response_schema_deserialize() {
         let chapter_response_schema = r#"{
@@ -126,7 +299,13 @@ mod tests {
                         "chapter": "chapter",
                         "volume": "volume",
                         "translatedLanguage": "en",
-                        "publishAt": "2024-01-01T00:00:00+00:00"
+                        "publishAt": "2024-01-01T00:00:00+00:00",
+                        "readableAt": "2024-01-01T00:00:00+00:00",
+                        "createdAt": "2023-12-31T00:00:00+00:00",
+                        "updatedAt": "2024-01-02T00:00:00+00:00",
+                        "externalUrl": null,
+                        "pages": 20,
+                        "isUnavailable": false
                     },
                     "relationships": [
                         {
@@ -156,6 +335,12 @@ mod tests {
                     volume: Some("volume".to_string()),
                     translated_language: "en".to_string(),
                     publish_at: "2024-01-01T00:00:00+00:00".to_string(),
+                    readable_at: Some("2024-01-01T00:00:00+00:00".to_string()),
+                    created_at: Some("2023-12-31T00:00:00+00:00".to_string()),
+                    updated_at: Some("2024-01-02T00:00:00+00:00".to_string()),
+                    external_url: None,
+                    pages: Some(20),
+                    is_unavailable: Some(false),
                 },
                 relationships: vec![ChapterRelationshipSchema {
                     id: "id".to_string(),
@@ -185,6 +370,12 @@ mod tests {
                     volume: Some("volume".to_string()),
                     translated_language: "en".to_string(),
                     publish_at: "2024-01-01T00:00:00+00:00".to_string(),
+                    readable_at: Some("2024-01-01T00:00:00+00:00".to_string()),
+                    created_at: Some("2023-12-31T00:00:00+00:00".to_string()),
+                    updated_at: Some("2024-01-02T00:00:00+00:00".to_string()),
+                    external_url: None,
+                    pages: Some(20),
+                    is_unavailable: Some(false),
                 },
                 relationships: vec![ChapterRelationshipSchema {
                     id: "id".to_string(),
@@ -211,6 +402,155 @@ mod tests {
             assert_eq!(chapter.scanlator, "scanlator");
             assert_eq!(chapter.url, format!("{}/chapter/id", HOME_URL));
             assert_eq!(chapter.language, "en");
+            assert_eq!(chapter.external_url, "");
+            assert!(chapter.available);
         }
     }
+
+    #[test]
+    fn test_deduplicate_chapters() {
+        // The publication dates are in the reverse order of the uploads
+        let chapter = |id, number, group, created_at: &str, publish_at| {
+            let mut chapter = chapter_data_schema(id, number, group, publish_at);
+            chapter.attributes.created_at = Some(created_at.to_string());
+            chapter
+        };
+        let chapters = || {
+            vec![
+                chapter(
+                    "a1",
+                    "1",
+                    "a",
+                    "2024-01-01T00:00:00+00:00",
+                    "2024-01-06T00:00:00+00:00",
+                ),
+                chapter(
+                    "b1",
+                    "1",
+                    "b",
+                    "2024-01-02T00:00:00+00:00",
+                    "2024-01-05T00:00:00+00:00",
+                ),
+                chapter(
+                    "c1",
+                    "1",
+                    "c",
+                    "2024-01-03T00:00:00+00:00",
+                    "2024-01-04T00:00:00+00:00",
+                ),
+                chapter_data_schema("a2", "2", "a", "2024-01-04T00:00:00+00:00"),
+            ]
+        };
+        let ids = |chapters: Vec<ChapterDataSchema>| {
+            chapters
+                .into_iter()
+                .map(|chapter| chapter.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(deduplicate_chapters(chapters(), 0, &[])),
+            vec!["a1", "b1", "c1", "a2"]
+        );
+        assert_eq!(
+            ids(deduplicate_chapters(chapters(), 1, &[])),
+            vec!["c1", "a2"]
+        );
+        assert_eq!(
+            ids(deduplicate_chapters(chapters(), 2, &[])),
+            vec!["a1", "a2"]
+        );
+
+        let preferred_groups = vec!["b".to_string(), "a".to_string()];
+        assert_eq!(
+            ids(deduplicate_chapters(chapters(), 1, &preferred_groups)),
+            vec!["b1", "a2"]
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_chapters_without_number() {
+        let mut oneshot = chapter_data_schema("a", "", "a", "2024-01-01T00:00:00+00:00");
+        oneshot.attributes.chapter = None;
+        let mut other_oneshot = chapter_data_schema("b", "", "b", "2024-01-01T00:00:00+00:00");
+        other_oneshot.attributes.chapter = None;
+
+        let chapters = deduplicate_chapters(vec![oneshot, other_oneshot], 1, &[]);
+
+        assert_eq!(chapters.len(), 2);
+    }
+
+    #[test]
+    fn test_chapter_data_schema_try_into_date_out_of_range() {
+        let chapter_data_schema = |publish_at| {
+            let mut chapter_data_schema = chapter_data_schema("id", "1", "group", publish_at);
+            chapter_data_schema.relationships.clear();
+            chapter_data_schema
+        };
+
+        let chapter: Chapter = chapter_data_schema("1969-12-31T00:00:00+00:00")
+            .try_into()
+            .unwrap();
+        assert_eq!(chapter.date_updated, 0);
+
+        let chapter: Chapter = chapter_data_schema("2200-01-01T00:00:00+00:00")
+            .try_into()
+            .unwrap();
+        assert_eq!(chapter.date_updated, u32::MAX);
+    }
+
+    #[test]
+    fn test_chapter_data_schema_try_into_external() {
+        let chapter_data_schema = ChapterDataSchema {
+            id: "id".to_string(),
+            attributes: ChapterAttributesSchema {
+                title: None,
+                chapter: Some("1".to_string()),
+                volume: None,
+                translated_language: "en".to_string(),
+                publish_at: "2024-01-01T00:00:00+00:00".to_string(),
+                readable_at: None,
+                created_at: None,
+                updated_at: None,
+                external_url: Some("https://mangaplus.shueisha.co.jp/viewer/1".to_string()),
+                pages: Some(0),
+                is_unavailable: Some(false),
+            },
+            relationships: vec![],
+        };
+
+        let chapter: Chapter = chapter_data_schema.try_into().unwrap();
+
+        assert_eq!(
+            chapter.external_url,
+            "https://mangaplus.shueisha.co.jp/viewer/1"
+        );
+        assert!(chapter.available);
+    }
+
+    #[test]
+    fn test_chapter_data_schema_try_into_unavailable() {
+        let chapter_data_schema = ChapterDataSchema {
+            id: "id".to_string(),
+            attributes: ChapterAttributesSchema {
+                title: None,
+                chapter: Some("1".to_string()),
+                volume: None,
+                translated_language: "en".to_string(),
+                publish_at: "2024-01-01T00:00:00+00:00".to_string(),
+                readable_at: None,
+                created_at: None,
+                updated_at: None,
+                external_url: None,
+                pages: Some(0),
+                is_unavailable: Some(true),
+            },
+            relationships: vec![],
+        };
+
+        let chapter: Chapter = chapter_data_schema.try_into().unwrap();
+
+        assert_eq!(chapter.external_url, "");
+        assert!(!chapter.available);
+    }
 }
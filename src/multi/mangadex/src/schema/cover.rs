@@ -0,0 +1,229 @@
+use miniserde::Deserialize;
+
+use crate::bindings::exports::midoku::types::cover::Cover;
+use crate::host_settings::HostSettings;
+use crate::utils::chapter_number::ChapterNumber;
+use crate::HOME_URL;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CoverResponseSchema {
+    pub data: Vec<CoverDataSchema>,
+    pub limit: isize,
+    pub offset: isize,
+    pub total: isize,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CoverDataSchema {
+    pub id: String,
+    pub attributes: CoverAttributesSchema,
+    pub relationships: Vec<CoverRelationshipSchema>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CoverAttributesSchema {
+    pub volume: Option<String>,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CoverRelationshipSchema {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub relationship_type: String,
+}
+
+impl CoverDataSchema {
+    /// Get the ID of the manga the cover belongs to.
+    pub fn manga_id(&self) -> Option<&str> {
+        self.relationships
+            .iter()
+            .find(|relationship| relationship.relationship_type == "manga")
+            .map(|relationship| relationship.id.as_str())
+    }
+
+    fn volume_number(&self) -> Option<f32> {
+        let volume = ChapterNumber::parse(self.attributes.volume.as_ref()?);
+        Some(volume.value).filter(|&value| value >= 0.0)
+    }
+}
+
+/// Get the URL of a cover file using the configured cover quality.
+pub fn cover_url(manga_id: &str, file_name: &str) -> String {
+    let cover_quality = match HostSettings::get_cover_quality() {
+        0 => "",
+        1 => ".512.jpg",
+        2 => ".256.jpg",
+        _ => "",
+    };
+
+    format!(
+        "{}/covers/{}/{}{}",
+        HOME_URL, manga_id, file_name, cover_quality
+    )
+}
+
+/// Select the cover of a manga according to the cover source setting.
+///
+/// `covers` must only contain the covers of a single manga. Returns `None`
+/// when the main cover should be kept.
+pub fn select_cover<'a>(
+    covers: &'a [CoverDataSchema],
+    cover_source: u64,
+    locale: &str,
+) -> Option<&'a CoverDataSchema> {
+    let by_volume = |a: &&CoverDataSchema, b: &&CoverDataSchema| {
+        a.volume_number()
+            .unwrap_or(f32::MIN)
+            .total_cmp(&b.volume_number().unwrap_or(f32::MIN))
+    };
+
+    match cover_source {
+        // First volume
+        1 => covers
+            .iter()
+            .filter(|cover| cover.volume_number().is_some())
+            .min_by(by_volume),
+        // Latest volume
+        2 => covers
+            .iter()
+            .filter(|cover| cover.volume_number().is_some())
+            .max_by(by_volume),
+        // Latest volume in the user's locale
+        3 => covers
+            .iter()
+            .filter(|cover| cover.attributes.locale.as_deref() == Some(locale))
+            .max_by(by_volume),
+        _ => None,
+    }
+}
+
+impl TryInto<Cover> for CoverDataSchema {
+    type Error = ();
+
+    fn try_into(self) -> Result<Cover, Self::Error> {
+        let manga_id = self.manga_id().ok_or(())?;
+        let url = format!(
+            "{}/covers/{}/{}",
+            HOME_URL, manga_id, self.attributes.file_name
+        );
+
+        Ok(Cover {
+            id: self.id,
+            volume: self.attributes.volume.unwrap_or_default(),
+            locale: self.attributes.locale.unwrap_or_default(),
+            medium_url: format!("{}.512.jpg", url),
+            low_url: format!("{}.256.jpg", url),
+            url,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cover_data_schema(id: &str, volume: Option<&str>, locale: &str) -> CoverDataSchema {
+        CoverDataSchema {
+            id: id.to_string(),
+            attributes: CoverAttributesSchema {
+                volume: volume.map(|volume| volume.to_string()),
+                file_name: format!("{}.jpg", id),
+                locale: Some(locale.to_string()),
+            },
+            relationships: vec![CoverRelationshipSchema {
+                id: "manga".to_string(),
+                relationship_type: "manga".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_cover_response_schema_deserialize() {
+        let cover_response_schema = r#"{
+            "data": [
+                {
+                    "id": "id",
+                    "attributes": {
+                        "volume": "1",
+                        "fileName": "file.jpg",
+                        "locale": "ja"
+                    },
+                    "relationships": [
+                        {
+                            "id": "manga",
+                            "type": "manga"
+                        }
+                    ]
+                }
+            ],
+            "limit": 1,
+            "offset": 0,
+            "total": 1
+        }"#;
+
+        let cover_response_schema: CoverResponseSchema =
+            miniserde::json::from_str(cover_response_schema).unwrap();
+
+        let expected = CoverResponseSchema {
+            data: vec![CoverDataSchema {
+                id: "id".to_string(),
+                attributes: CoverAttributesSchema {
+                    volume: Some("1".to_string()),
+                    file_name: "file.jpg".to_string(),
+                    locale: Some("ja".to_string()),
+                },
+                relationships: vec![CoverRelationshipSchema {
+                    id: "manga".to_string(),
+                    relationship_type: "manga".to_string(),
+                }],
+            }],
+            limit: 1,
+            offset: 0,
+            total: 1,
+        };
+
+        assert_eq!(cover_response_schema, expected);
+    }
+
+    #[test]
+    fn test_cover_data_schema_try_into() {
+        let cover: Cover = cover_data_schema("id", Some("1"), "ja").try_into().unwrap();
+
+        assert_eq!(cover.id, "id");
+        assert_eq!(cover.volume, "1");
+        assert_eq!(cover.locale, "ja");
+        assert_eq!(cover.url, format!("{}/covers/manga/id.jpg", HOME_URL));
+        assert_eq!(
+            cover.medium_url,
+            format!("{}/covers/manga/id.jpg.512.jpg", HOME_URL)
+        );
+        assert_eq!(
+            cover.low_url,
+            format!("{}/covers/manga/id.jpg.256.jpg", HOME_URL)
+        );
+    }
+
+    #[test]
+    fn test_select_cover() {
+        let covers = vec![
+            cover_data_schema("none", None, "ja"),
+            cover_data_schema("ja-2", Some("2"), "ja"),
+            cover_data_schema("ja-10", Some("10"), "ja"),
+            cover_data_schema("en-1", Some("1"), "en"),
+            cover_data_schema("en-3", Some("3"), "en"),
+        ];
+
+        let select = |cover_source, locale| {
+            select_cover(&covers, cover_source, locale).map(|cover| cover.id.as_str())
+        };
+
+        assert_eq!(select(0, "en"), None);
+        assert_eq!(select(1, "en"), Some("en-1"));
+        assert_eq!(select(2, "en"), Some("ja-10"));
+        assert_eq!(select(3, "en"), Some("en-3"));
+        assert_eq!(select(3, "fr"), None);
+    }
+}
@@ -0,0 +1,123 @@
+use miniserde::{Deserialize, Serialize};
+
+use crate::bindings::exports::midoku::types::custom_list::{CustomList, Visibility};
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomListResponseSchema {
+    pub data: Vec<CustomListDataSchema>,
+    pub limit: isize,
+    pub offset: isize,
+    pub total: isize,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomListResponseSingleSchema {
+    pub data: CustomListDataSchema,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomListDataSchema {
+    pub id: String,
+    pub attributes: CustomListAttributesSchema,
+    pub relationships: Vec<CustomListRelationshipSchema>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomListAttributesSchema {
+    pub name: String,
+    pub visibility: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct CustomListRelationshipSchema {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub relationship_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomListRequestSchema {
+    pub name: String,
+    pub visibility: String,
+    pub manga: Vec<String>,
+}
+
+/// Get the name of a visibility in the API.
+pub fn visibility_name(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Private => "private",
+    }
+}
+
+impl TryInto<CustomList> for CustomListDataSchema {
+    type Error = ();
+
+    fn try_into(self) -> Result<CustomList, Self::Error> {
+        let visibility = match self.attributes.visibility.as_str() {
+            "public" => Visibility::Public,
+            "private" => Visibility::Private,
+            _ => return Err(()),
+        };
+
+        let manga_ids = self
+            .relationships
+            .into_iter()
+            .filter(|relationship| relationship.relationship_type == "manga")
+            .map(|relationship| relationship.id)
+            .collect();
+
+        Ok(CustomList {
+            id: self.id,
+            name: self.attributes.name,
+            visibility,
+            manga_ids,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_list_data_schema_try_into() {
+        let custom_list_data_schema = r#"{
+            "id": "list",
+            "type": "custom_list",
+            "attributes": {
+                "name": "Favorites",
+                "visibility": "private",
+                "version": 1
+            },
+            "relationships": [
+                { "id": "manga-1", "type": "manga" },
+                { "id": "user", "type": "user" },
+                { "id": "manga-2", "type": "manga" }
+            ]
+        }"#;
+
+        let custom_list_data_schema: CustomListDataSchema =
+            miniserde::json::from_str(custom_list_data_schema).unwrap();
+        let custom_list: CustomList = custom_list_data_schema.try_into().unwrap();
+
+        assert_eq!(custom_list.id, "list");
+        assert_eq!(custom_list.name, "Favorites");
+        assert_eq!(custom_list.visibility, Visibility::Private);
+        assert_eq!(custom_list.manga_ids, vec!["manga-1", "manga-2"]);
+    }
+
+    #[test]
+    fn test_custom_list_request_schema_serialize() {
+        let custom_list_request_schema = CustomListRequestSchema {
+            name: "Favorites".to_string(),
+            visibility: visibility_name(Visibility::Public).to_string(),
+            manga: vec!["manga-1".to_string()],
+        };
+
+        assert_eq!(
+            miniserde::json::to_string(&custom_list_request_schema),
+            r#"{"name":"Favorites","visibility":"public","manga":["manga-1"]}"#
+        );
+    }
+}
@@ -2,6 +2,8 @@ use miniserde::{json, Deserialize};
 
 use crate::bindings::exports::midoku::types::manga::{ContentRating, Manga, ReadingMode, Status};
 use crate::host_settings::HostSettings;
+use crate::schema::cover::cover_url;
+use crate::utils::description::sanitize_description;
 use crate::HOME_URL;
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -162,6 +164,13 @@ impl TryInto<Manga> for MangaDataSchema {
         let description =
             object_get_string!(self.attributes.description, &locale).unwrap_or(String::new());
 
+        // Hosts that can render markdown may ask for the raw description
+        let description = if HostSettings::get_raw_description() {
+            description
+        } else {
+            sanitize_description(&description)
+        };
+
         let mut cover_file = String::new();
         let mut author_name = String::new();
         let mut artist_name = String::new();
@@ -189,17 +198,10 @@ impl TryInto<Manga> for MangaDataSchema {
             }
         }
 
-        let cover_quality = match HostSettings::get_cover_quality() {
-            0 => "",
-            1 => ".512.jpg",
-            2 => ".256.jpg",
-            _ => "",
-        };
-
         let cover_url = if cover_file.is_empty() {
             Default::default()
         } else {
-            format!("{}/covers/{}/{}{}", HOME_URL, id, cover_file, cover_quality)
+            cover_url(&id, &cover_file)
         };
 
         let status = match self.attributes.status.as_str() {
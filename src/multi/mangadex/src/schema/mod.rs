@@ -1,3 +1,26 @@
+use miniserde::json;
+
+pub mod aggregate;
+pub mod auth;
 pub mod chapter;
+pub mod cover;
+pub mod custom_list;
 pub mod manga;
 pub mod page;
+pub mod rating;
+pub mod read;
+pub mod report;
+pub mod result;
+pub mod status;
+
+/// Get the entries of an object keyed by manga ID.
+///
+/// The API returns an empty array instead of an empty object when there are
+/// no entries.
+pub fn manga_entries(value: &json::Value) -> Result<Vec<(&String, &json::Value)>, ()> {
+    match value {
+        json::Value::Object(object) => Ok(object.iter().collect()),
+        json::Value::Array(array) if array.is_empty() => Ok(vec![]),
+        _ => Err(()),
+    }
+}
@@ -3,14 +3,14 @@ use miniserde::Deserialize;
 use crate::bindings::exports::midoku::types::page::Page;
 use crate::host_settings::HostSettings;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct PageResponseSchema {
     #[serde(rename = "baseUrl")]
     pub base_url: String,
     pub chapter: PageChapterSchema,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct PageChapterSchema {
     pub hash: String,
     pub data: Vec<String>,
@@ -35,12 +35,16 @@ impl Into<Vec<Page>> for PageResponseSchema {
             self.chapter.data
         };
 
+        // Identify the client the same way the API requests do
+        let headers = vec![("User-Agent".to_string(), HostSettings::get_user_agent())];
+
         let mut page_list = Vec::with_capacity(data.len());
         for (index, file_name) in data.into_iter().enumerate() {
             page_list.push(Page {
                 index: index as u32,
                 url: format!("{}/{}", base_url, file_name),
                 base64: Default::default(),
+                headers: Some(headers.clone()),
             });
         }
 
@@ -96,5 +100,9 @@ mod tests {
         assert_eq!(page_list[0].url, "https://api.mangadex.org/data/hash/1.jpg");
         assert_eq!(page_list[1].index, 1);
         assert_eq!(page_list[1].url, "https://api.mangadex.org/data/hash/2.jpg");
+        assert_eq!(
+            page_list[1].headers,
+            Some(vec![("User-Agent".to_string(), "Midoku".to_string())])
+        );
     }
 }
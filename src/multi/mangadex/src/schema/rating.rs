@@ -0,0 +1,66 @@
+use miniserde::{json, Deserialize, Serialize};
+
+use crate::schema::manga_entries;
+
+#[derive(Debug, Deserialize)]
+pub struct RatingResponseSchema {
+    /// The ratings keyed by manga ID, each an object with a `rating` field.
+    pub ratings: json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RatingRequestSchema {
+    pub rating: u8,
+}
+
+impl RatingResponseSchema {
+    /// Get the rating of each manga.
+    pub fn ratings(&self) -> Result<Vec<(String, u8)>, ()> {
+        manga_entries(&self.ratings)?
+            .into_iter()
+            .map(|(manga_id, rating)| {
+                let rating = match rating {
+                    json::Value::Object(rating) => rating.get("rating"),
+                    _ => None,
+                };
+
+                match rating {
+                    Some(json::Value::Number(json::Number::U64(rating))) => {
+                        let rating = u8::try_from(*rating).map_err(|_| ())?;
+                        Ok((manga_id.clone(), rating))
+                    }
+                    _ => Err(()),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rating_response_schema_ratings() {
+        let rating_response_schema = r#"{
+            "result": "ok",
+            "ratings": {
+                "manga-1": { "rating": 9, "createdAt": "2024-01-01T00:00:00+00:00" },
+                "manga-2": { "rating": 4, "createdAt": "2024-06-15T12:34:56+00:00" }
+            }
+        }"#;
+
+        let rating_response_schema: RatingResponseSchema =
+            miniserde::json::from_str(rating_response_schema).unwrap();
+
+        assert_eq!(
+            rating_response_schema.ratings(),
+            Ok(vec![("manga-1".to_string(), 9), ("manga-2".to_string(), 4)])
+        );
+
+        let rating_response_schema: RatingResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "ok", "ratings": [] }"#).unwrap();
+
+        assert_eq!(rating_response_schema.ratings(), Ok(vec![]));
+    }
+}
@@ -0,0 +1,90 @@
+use miniserde::{json, Deserialize, Serialize};
+
+use crate::schema::manga_entries;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct ReadResponseSchema {
+    pub data: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadBatchResponseSchema {
+    /// The read chapters keyed by manga ID.
+    pub data: json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadRequestSchema {
+    #[serde(rename = "chapterIdsRead")]
+    pub chapter_ids_read: Vec<String>,
+    #[serde(rename = "chapterIdsUnread")]
+    pub chapter_ids_unread: Vec<String>,
+}
+
+impl ReadBatchResponseSchema {
+    /// Get the IDs of the read chapters of each manga.
+    pub fn read_chapters(&self) -> Result<Vec<(String, Vec<String>)>, ()> {
+        manga_entries(&self.data)?
+            .into_iter()
+            .map(|(manga_id, chapter_ids)| match chapter_ids {
+                json::Value::Array(chapter_ids) => chapter_ids
+                    .iter()
+                    .map(|chapter_id| match chapter_id {
+                        json::Value::String(chapter_id) => Ok(chapter_id.clone()),
+                        _ => Err(()),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(|chapter_ids| (manga_id.clone(), chapter_ids)),
+                _ => Err(()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_batch_response_schema_read_chapters() {
+        let read_batch_response_schema = r#"{
+            "result": "ok",
+            "data": {
+                "manga-1": ["chapter-1", "chapter-2"],
+                "manga-2": []
+            }
+        }"#;
+
+        let read_batch_response_schema: ReadBatchResponseSchema =
+            miniserde::json::from_str(read_batch_response_schema).unwrap();
+
+        assert_eq!(
+            read_batch_response_schema.read_chapters(),
+            Ok(vec![
+                (
+                    "manga-1".to_string(),
+                    vec!["chapter-1".to_string(), "chapter-2".to_string()]
+                ),
+                ("manga-2".to_string(), vec![]),
+            ])
+        );
+
+        let read_batch_response_schema: ReadBatchResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "ok", "data": [] }"#).unwrap();
+
+        assert_eq!(read_batch_response_schema.read_chapters(), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_read_request_schema_serialize() {
+        let read_request_schema = ReadRequestSchema {
+            chapter_ids_read: vec!["chapter-1".to_string()],
+            chapter_ids_unread: vec![],
+        };
+
+        assert_eq!(
+            miniserde::json::to_string(&read_request_schema),
+            r#"{"chapterIdsRead":["chapter-1"],"chapterIdsUnread":[]}"#
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use miniserde::Serialize;
+
+pub const REPORT_URL: &str = "https://api.mangadex.network/report";
+
+/// The body of a MangaDex@Home health report.
+#[derive(Debug, Serialize)]
+pub struct ReportSchema {
+    pub url: String,
+    pub success: bool,
+    pub bytes: u32,
+    pub duration: u32,
+    pub cached: bool,
+}
+
+impl ReportSchema {
+    /// Whether the image was served by a MangaDex@Home node.
+    ///
+    /// Images served by the MangaDex servers themselves (e.g.
+    /// `uploads.mangadex.org`) must not be reported.
+    pub fn should_report(&self) -> bool {
+        let Some((_, rest)) = self.url.split_once("://") else {
+            return false;
+        };
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default();
+
+        !host.is_empty() && host != "mangadex.org" && !host.ends_with(".mangadex.org")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report_schema(url: &str) -> ReportSchema {
+        ReportSchema {
+            url: url.to_string(),
+            success: true,
+            bytes: 1024,
+            duration: 200,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_report_schema_should_report() {
+        assert!(
+            report_schema("https://abc.xyz.mangadex.network:443/data/hash/1.jpg").should_report()
+        );
+        assert!(report_schema("https://abc.xyz.mangadex.network/data/hash/1.jpg").should_report());
+        assert!(!report_schema("https://uploads.mangadex.org/data/hash/1.jpg").should_report());
+        assert!(!report_schema("https://mangadex.org/data/hash/1.jpg").should_report());
+        assert!(!report_schema("not a url").should_report());
+    }
+
+    #[test]
+    fn test_report_schema_serialize() {
+        let report_schema = report_schema("https://abc.xyz.mangadex.network/data/hash/1.jpg");
+
+        assert_eq!(
+            miniserde::json::to_string(&report_schema),
+            r#"{"url":"https://abc.xyz.mangadex.network/data/hash/1.jpg","success":true,"bytes":1024,"duration":200,"cached":false}"#
+        );
+    }
+}
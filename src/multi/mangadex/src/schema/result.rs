@@ -0,0 +1,30 @@
+use miniserde::Deserialize;
+
+/// The response of the API requests that return no data.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct ResultResponseSchema {
+    pub result: String,
+}
+
+impl ResultResponseSchema {
+    pub fn is_ok(&self) -> bool {
+        self.result == "ok"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_response_schema_is_ok() {
+        let result_response_schema: ResultResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "ok" }"#).unwrap();
+        assert!(result_response_schema.is_ok());
+
+        let result_response_schema: ResultResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "error", "errors": [{ "status": 403 }] }"#)
+                .unwrap();
+        assert!(!result_response_schema.is_ok());
+    }
+}
@@ -0,0 +1,136 @@
+use miniserde::{json, Deserialize, Serialize};
+
+use crate::bindings::exports::midoku::types::manga::ReadingStatus;
+use crate::schema::manga_entries;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct StatusResponseSchema {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusesResponseSchema {
+    /// The reading statuses keyed by manga ID.
+    pub statuses: json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusRequestSchema {
+    pub status: Option<String>,
+}
+
+/// Parse a reading status of the API.
+pub fn parse_reading_status(status: &str) -> Option<ReadingStatus> {
+    match status {
+        "reading" => Some(ReadingStatus::Reading),
+        "on_hold" => Some(ReadingStatus::OnHold),
+        "plan_to_read" => Some(ReadingStatus::PlanToRead),
+        "dropped" => Some(ReadingStatus::Dropped),
+        "re_reading" => Some(ReadingStatus::ReReading),
+        "completed" => Some(ReadingStatus::Completed),
+        _ => None,
+    }
+}
+
+/// Get the name of a reading status in the API.
+pub fn reading_status_name(reading_status: ReadingStatus) -> &'static str {
+    match reading_status {
+        ReadingStatus::Reading => "reading",
+        ReadingStatus::OnHold => "on_hold",
+        ReadingStatus::PlanToRead => "plan_to_read",
+        ReadingStatus::Dropped => "dropped",
+        ReadingStatus::ReReading => "re_reading",
+        ReadingStatus::Completed => "completed",
+    }
+}
+
+impl StatusResponseSchema {
+    pub fn reading_status(&self) -> Option<ReadingStatus> {
+        self.status.as_deref().and_then(parse_reading_status)
+    }
+}
+
+impl StatusesResponseSchema {
+    /// Get the reading status of each manga, leaving out the unknown ones.
+    pub fn reading_statuses(&self) -> Result<Vec<(String, ReadingStatus)>, ()> {
+        Ok(manga_entries(&self.statuses)?
+            .into_iter()
+            .filter_map(|(manga_id, status)| match status {
+                json::Value::String(status) => parse_reading_status(status)
+                    .map(|reading_status| (manga_id.clone(), reading_status)),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_status_round_trip() {
+        let reading_statuses = [
+            ReadingStatus::Reading,
+            ReadingStatus::OnHold,
+            ReadingStatus::PlanToRead,
+            ReadingStatus::Dropped,
+            ReadingStatus::ReReading,
+            ReadingStatus::Completed,
+        ];
+
+        for reading_status in reading_statuses {
+            assert_eq!(
+                parse_reading_status(reading_status_name(reading_status)),
+                Some(reading_status)
+            );
+        }
+
+        assert_eq!(parse_reading_status("unknown"), None);
+    }
+
+    #[test]
+    fn test_statuses_response_schema_reading_statuses() {
+        let statuses_response_schema = r#"{
+            "result": "ok",
+            "statuses": {
+                "manga-1": "reading",
+                "manga-2": "plan_to_read",
+                "manga-3": "unknown"
+            }
+        }"#;
+
+        let statuses_response_schema: StatusesResponseSchema =
+            miniserde::json::from_str(statuses_response_schema).unwrap();
+
+        assert_eq!(
+            statuses_response_schema.reading_statuses(),
+            Ok(vec![
+                ("manga-1".to_string(), ReadingStatus::Reading),
+                ("manga-2".to_string(), ReadingStatus::PlanToRead),
+            ])
+        );
+
+        let statuses_response_schema: StatusesResponseSchema =
+            miniserde::json::from_str(r#"{ "result": "ok", "statuses": [] }"#).unwrap();
+
+        assert_eq!(statuses_response_schema.reading_statuses(), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_status_request_schema_serialize() {
+        let status_request_schema = StatusRequestSchema {
+            status: Some("on_hold".to_string()),
+        };
+        assert_eq!(
+            miniserde::json::to_string(&status_request_schema),
+            r#"{"status":"on_hold"}"#
+        );
+
+        let status_request_schema = StatusRequestSchema { status: None };
+        assert_eq!(
+            miniserde::json::to_string(&status_request_schema),
+            r#"{"status":null}"#
+        );
+    }
+}
@@ -0,0 +1,130 @@
+/// A chapter or volume number as written by the uploader, along with the
+/// numeric value used to sort it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterNumber {
+    /// The numeric value of the number, or `-1.0` when it has none.
+    pub value: f32,
+
+    /// The number as it should be displayed to the user.
+    pub display: String,
+}
+
+impl ChapterNumber {
+    /// Parse a chapter or volume number.
+    ///
+    /// Only a single number gets a value, optionally after a label like
+    /// "Ch." or "Vol.". Commas are accepted as decimal separators and
+    /// full-width digits are supported.
+    ///
+    /// Sub-chapters ("10a"), ranges ("10-11") and labeled extras ("Extra 2")
+    /// get `-1.0`: any value would be shared with a real chapter and mark it
+    /// as read along with them. They are told apart by their display string.
+    pub fn parse(number: &str) -> Self {
+        let display = number.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let normalized: String = display
+            .chars()
+            .map(|c| match c {
+                // Full-width digits and punctuation
+                '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap(),
+                '．' => '.',
+                '，' => ',',
+                c => c,
+            })
+            .collect();
+
+        let value = parse_value(strip_label(&normalized)).unwrap_or(-1.0);
+
+        ChapterNumber { value, display }
+    }
+}
+
+/// Remove the chapter or volume label in front of a number, if any.
+fn strip_label(number: &str) -> &str {
+    const LABELS: [&str; 6] = ["chapter", "volume", "episode", "ch", "vol", "ep"];
+
+    for label in LABELS {
+        let Some(prefix) = number.get(..label.len()) else {
+            continue;
+        };
+        if !prefix.eq_ignore_ascii_case(label) {
+            continue;
+        }
+
+        let rest = number[label.len()..].trim_start_matches('.').trim_start();
+        if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return rest;
+        }
+    }
+
+    number
+}
+
+/// Parse a number made of an integer part and an optional decimal part,
+/// with nothing around it.
+fn parse_value(number: &str) -> Option<f32> {
+    let (integer, fraction) = match number.split_once(['.', ',']) {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (number, ""),
+    };
+
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    format!("{}.{}", integer, fraction).parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chapter_number_parse() {
+        let cases: &[(&str, f32, &str)] = &[
+            // Plain numbers
+            ("1", 1.0, "1"),
+            ("0", 0.0, "0"),
+            ("0.5", 0.5, "0.5"),
+            ("100.1", 100.1, "100.1"),
+            ("007", 7.0, "007"),
+            ("12.", 12.0, "12."),
+            // Stray whitespace
+            (" 12 ", 12.0, "12"),
+            ("0.5\n", 0.5, "0.5"),
+            // Decimal commas and full-width digits
+            ("1,5", 1.5, "1,5"),
+            ("１２", 12.0, "１２"),
+            ("１２．５", 12.5, "１２．５"),
+            // Labels
+            ("Ch. 5", 5.0, "Ch. 5"),
+            ("Chapter 12.5", 12.5, "Chapter 12.5"),
+            ("vol.3", 3.0, "vol.3"),
+            // Sub-chapters, which would share the value of 10.1 and 10.2
+            ("10a", -1.0, "10a"),
+            ("10B", -1.0, "10B"),
+            ("10 a", -1.0, "10 a"),
+            ("5end", -1.0, "5end"),
+            // Ranges, which would share the value of their first chapter
+            ("10-11", -1.0, "10-11"),
+            ("10 - 11", -1.0, "10 - 11"),
+            ("10~11", -1.0, "10~11"),
+            ("12+13", -1.0, "12+13"),
+            ("45.6.1", -1.0, "45.6.1"),
+            // Extras, which would share the value of the chapter they follow
+            ("Extra 2", -1.0, "Extra 2"),
+            ("Special 1.5", -1.0, "Special 1.5"),
+            ("Extra", -1.0, "Extra"),
+            ("EX", -1.0, "EX"),
+            ("", -1.0, ""),
+            ("   ", -1.0, ""),
+        ];
+
+        for &(number, value, display) in cases {
+            let chapter_number = ChapterNumber::parse(number);
+            assert_eq!(chapter_number.value, value, "value of {:?}", number);
+            assert_eq!(chapter_number.display, display, "display of {:?}", number);
+        }
+    }
+}
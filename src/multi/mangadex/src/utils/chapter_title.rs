@@ -0,0 +1,117 @@
+/// Format a chapter title from a template.
+///
+/// The template may contain the `{volume}`, `{chapter}` and `{title}`
+/// placeholders. Each placeholder owns the text written before it, and the
+/// last one also owns the text written after it, so that a missing part is
+/// removed along with its label. For example `Vol.{volume} Ch.{chapter} -
+/// {title}` gives `Ch.5 - Title` when the volume is missing, and `Vol.1 Ch.5`
+/// when the title is missing.
+pub fn format_chapter_title(template: &str, volume: &str, chapter: &str, title: &str) -> String {
+    let mut formatted = String::new();
+    let mut literal = String::new();
+    let mut dropped = false;
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        literal.push_str(&rest[..start]);
+
+        let placeholder = rest[start + 1..]
+            .find('}')
+            .map(|end| &rest[start + 1..start + 1 + end]);
+
+        let value = match placeholder {
+            Some("volume") => volume,
+            Some("chapter") => chapter,
+            Some("title") => title,
+            _ => {
+                // Not a placeholder, keep the brace as is
+                literal.push('{');
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+        rest = &rest[start + placeholder.unwrap().len() + 2..];
+
+        let value = value.trim();
+        if value.is_empty() {
+            literal.clear();
+            dropped = true;
+            continue;
+        }
+
+        // Remove the separator left dangling by the missing leading parts
+        let literal_text = if formatted.is_empty() && dropped {
+            literal.trim_start_matches(is_separator)
+        } else {
+            literal.as_str()
+        };
+
+        formatted.push_str(literal_text);
+        formatted.push_str(value);
+        literal.clear();
+        dropped = false;
+    }
+
+    if !dropped && !formatted.is_empty() {
+        formatted.push_str(&literal);
+        formatted.push_str(rest);
+    }
+
+    formatted.trim().to_string()
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '-' | '–' | '—' | ':' | '|' | ',' | '.' | '/' | '·')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_chapter_title() {
+        let template = "Vol.{volume} Ch.{chapter} - {title}";
+        let cases: &[(&str, &str, &str, &str)] = &[
+            ("1", "5", "Title", "Vol.1 Ch.5 - Title"),
+            ("", "5", "Title", "Ch.5 - Title"),
+            ("1", "", "Title", "Vol.1 - Title"),
+            ("1", "5", "", "Vol.1 Ch.5"),
+            ("", "", "Title", "Title"),
+            ("", "5", "", "Ch.5"),
+            ("", "", "", ""),
+        ];
+
+        for &(volume, chapter, title, expected) in cases {
+            assert_eq!(
+                format_chapter_title(template, volume, chapter, title),
+                expected,
+                "volume {:?}, chapter {:?}, title {:?}",
+                volume,
+                chapter,
+                title
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_chapter_title_trailing_text() {
+        let template = "{title} ({chapter})";
+
+        assert_eq!(
+            format_chapter_title(template, "", "5", "Title"),
+            "Title (5)"
+        );
+        assert_eq!(format_chapter_title(template, "", "", "Title"), "Title");
+        assert_eq!(format_chapter_title(template, "", "5", ""), "(5)");
+    }
+
+    #[test]
+    fn test_format_chapter_title_literal_braces() {
+        assert_eq!(format_chapter_title("{title}", "1", "5", "Title"), "Title");
+        assert_eq!(
+            format_chapter_title("{unknown} {title}", "", "", "Title"),
+            "{unknown} Title"
+        );
+        assert_eq!(format_chapter_title("Ch.{chapter", "", "5", ""), "");
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::utils::datetime::parse_http_date;
+
+/// The Unix timestamp in seconds of the latest response, or 0 if unknown.
+static LATEST_RESPONSE_DATE: AtomicU32 = AtomicU32::new(0);
+
+/// Record the `Date` header of a response.
+pub fn observe_date_header(headers: &[(String, String)]) {
+    let date = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("date"))
+        .and_then(|(_, value)| parse_http_date(value));
+
+    if let Some(date) = date {
+        LATEST_RESPONSE_DATE.fetch_max(date, Ordering::Relaxed);
+    }
+}
+
+/// Get the current Unix timestamp in seconds.
+///
+/// Extensions have no access to a clock, so the time is taken from the `Date`
+/// header of the latest response. It only moves forward when requests are
+/// sent. Returns `None` if no response had a date yet.
+pub fn now() -> Option<u32> {
+    match LATEST_RESPONSE_DATE.load(Ordering::Relaxed) {
+        0 => None,
+        date => Some(date),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_date_header() {
+        observe_date_header(&[("Content-Type".to_string(), "text/plain".to_string())]);
+        observe_date_header(&[(
+            "date".to_string(),
+            "Mon, 01 Jan 2024 00:00:00 GMT".to_string(),
+        )]);
+        assert!(now() >= Some(1704067200));
+
+        // The clock never goes back
+        observe_date_header(&[(
+            "Date".to_string(),
+            "Sun, 31 Dec 2023 00:00:00 GMT".to_string(),
+        )]);
+        assert!(now() >= Some(1704067200));
+    }
+}
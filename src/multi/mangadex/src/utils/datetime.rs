@@ -0,0 +1,114 @@
+use speedate::DateTime;
+
+/// Format a Unix timestamp in seconds the way the API expects dates in its
+/// query parameters (e.g. `updatedAtSince`), that is `YYYY-MM-DDTHH:MM:SS`.
+pub fn format_timestamp(timestamp: u32) -> String {
+    let days = timestamp / 86400;
+    let seconds = timestamp % 86400;
+
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Parse an HTTP date (e.g. `Sun, 06 Nov 1994 08:49:37 GMT`) to a Unix
+/// timestamp in seconds.
+pub fn parse_http_date(date: &str) -> Option<u32> {
+    let [_, day, month, year, time, "GMT"] = date.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+
+    let month = match month {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+
+    let date = format!("{}-{:02}-{:0>2}T{}Z", year, month, day, time);
+    let timestamp = DateTime::parse_str_rfc3339(&date).ok()?.timestamp();
+
+    Some(clamp_timestamp(timestamp))
+}
+
+/// Convert a Unix timestamp in seconds to the `u32` timestamps used by the
+/// types, saturating the dates before 1970 and after 2106.
+pub fn clamp_timestamp(timestamp: i64) -> u32 {
+    timestamp.clamp(0, u32::MAX as i64) as u32
+}
+
+/// Convert a number of days since 1970-01-01 to a (year, month, day) date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: u32) -> (u32, u32, u32) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u32;
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00");
+        assert_eq!(format_timestamp(951782400), "2000-02-29T00:00:00");
+        assert_eq!(format_timestamp(1704067200), "2024-01-01T00:00:00");
+        assert_eq!(format_timestamp(1718454896), "2024-06-15T12:34:56");
+        assert_eq!(format_timestamp(u32::MAX), "2106-02-07T06:28:15");
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Mon, 01 Jan 2024 00:00:00 GMT"),
+            Some(1704067200)
+        );
+        assert_eq!(
+            parse_http_date("Sat, 15 Jun 2024 12:34:56 GMT"),
+            Some(1718454896)
+        );
+        assert_eq!(parse_http_date("Sat, 15 Foo 2024 12:34:56 GMT"), None);
+        assert_eq!(parse_http_date("2024-06-15T12:34:56Z"), None);
+        assert_eq!(parse_http_date(""), None);
+    }
+
+    #[test]
+    fn test_clamp_timestamp() {
+        assert_eq!(clamp_timestamp(-1), 0);
+        assert_eq!(clamp_timestamp(1704067200), 1704067200);
+        assert_eq!(clamp_timestamp(u32::MAX as i64 + 1), u32::MAX);
+    }
+}
@@ -0,0 +1,350 @@
+/// Convert a MangaDex description to plain text.
+///
+/// MangaDex descriptions are written in a mix of markdown and BBCode. Links
+/// are moved to a footer, formatting markers are removed and HTML entities
+/// are decoded. Everything after the first `---` separator is usually a block
+/// of translator credits and external links, so only its links are kept.
+pub fn sanitize_description(description: &str) -> String {
+    let description = description.replace("\r\n", "\n");
+
+    let mut links = Vec::new();
+    let mut lines = Vec::new();
+    let mut after_separator = false;
+
+    for line in description.lines() {
+        if is_separator(line) {
+            after_separator = true;
+            continue;
+        }
+
+        let line = render_line(line, &mut links);
+
+        // Only collect the links of the credits block
+        if !after_separator {
+            lines.push(line);
+        }
+    }
+
+    let mut sanitized = String::new();
+    let mut blank = false;
+    for line in lines {
+        if line.is_empty() {
+            blank = !sanitized.is_empty();
+            continue;
+        }
+
+        if blank {
+            sanitized.push_str("\n\n");
+        } else if !sanitized.is_empty() {
+            sanitized.push('\n');
+        }
+        sanitized.push_str(&line);
+        blank = false;
+    }
+
+    if !links.is_empty() {
+        if !sanitized.is_empty() {
+            sanitized.push_str("\n\n");
+        }
+        sanitized.push_str("Links:");
+        for (text, url) in links {
+            if text.is_empty() || text == url {
+                sanitized.push_str(&format!("\n- {}", url));
+            } else {
+                sanitized.push_str(&format!("\n- {}: {}", text, url));
+            }
+        }
+    }
+
+    decode_html_entities(&sanitized)
+}
+
+/// Check whether a line is a markdown thematic break (`---`, `***`, `___`).
+fn is_separator(line: &str) -> bool {
+    let markers: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    markers.len() >= 3
+        && matches!(markers[0], '-' | '*' | '_')
+        && markers.iter().all(|&c| c == markers[0])
+}
+
+/// Render a single line, stripping the block-level markers.
+fn render_line(line: &str, links: &mut Vec<(String, String)>) -> String {
+    let mut line = line.trim();
+
+    // Blockquotes and headers
+    line = line.trim_start_matches('>').trim_start();
+    if line.starts_with('#') {
+        line = line.trim_start_matches('#').trim_start();
+    }
+
+    // List bullets are normalized to dashes
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return format!("- {}", render_inline(item.trim_start(), links));
+        }
+    }
+
+    render_inline(line, links)
+}
+
+/// Render inline markdown and BBCode, moving the links to `links`.
+fn render_inline(text: &str, links: &mut Vec<(String, String)>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut rendered = String::with_capacity(text.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() => {
+                rendered.push(chars[i + 1]);
+                i += 2;
+            }
+            '[' => {
+                if let Some((consumed, link_text, url)) = parse_markdown_link(&chars[i..]) {
+                    let link_text = render_inline(&link_text, links);
+                    rendered.push_str(&link_text);
+                    push_link(links, link_text, url);
+                    i += consumed;
+                } else if let Some((consumed, tag)) = parse_bbcode_tag(&chars[i..]) {
+                    match tag {
+                        BBCodeTag::Url(Some(url)) => {
+                            let (inner, consumed_inner) = bbcode_inner(&chars[i + consumed..]);
+                            let link_text = render_inline(&inner, links);
+                            rendered.push_str(&link_text);
+                            push_link(links, link_text, url);
+                            i += consumed_inner;
+                        }
+                        BBCodeTag::Url(None) => {
+                            let (inner, consumed_inner) = bbcode_inner(&chars[i + consumed..]);
+                            let url = inner.trim().to_string();
+                            rendered.push_str(&url);
+                            push_link(links, url.clone(), url);
+                            i += consumed_inner;
+                        }
+                        BBCodeTag::Formatting => {}
+                    }
+                    i += consumed;
+                } else {
+                    rendered.push('[');
+                    i += 1;
+                }
+            }
+            '*' => {
+                // Emphasis markers
+                i += 1;
+            }
+            '_' | '~' if chars.get(i + 1) == Some(&chars[i]) => {
+                // Underline and strikethrough markers
+                i += 2;
+            }
+            c => {
+                rendered.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    rendered.trim().to_string()
+}
+
+fn push_link(links: &mut Vec<(String, String)>, text: String, url: String) {
+    if url.is_empty() || links.iter().any(|(_, other)| *other == url) {
+        return;
+    }
+    links.push((text, url));
+}
+
+/// Parse a `[text](url)` link, returning the number of consumed characters.
+fn parse_markdown_link(chars: &[char]) -> Option<(usize, String, String)> {
+    let text_end = chars.iter().position(|&c| c == ']')?;
+    if chars.get(text_end + 1) != Some(&'(') {
+        return None;
+    }
+
+    // Urls may contain balanced parentheses (e.g. Wikipedia links)
+    let mut depth = 0;
+    let mut url_end = None;
+    for (offset, &c) in chars[text_end + 2..].iter().enumerate() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                url_end = Some(text_end + 2 + offset);
+                break;
+            }
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    let url_end = url_end?;
+
+    let text: String = chars[1..text_end].iter().collect();
+    let url: String = chars[text_end + 2..url_end].iter().collect();
+
+    Some((url_end + 1, text, url.trim().to_string()))
+}
+
+enum BBCodeTag {
+    Url(Option<String>),
+    Formatting,
+}
+
+const BBCODE_FORMATTING_TAGS: [&str; 10] = [
+    "b", "i", "u", "s", "spoiler", "center", "quote", "code", "hr", "img",
+];
+
+/// Parse a BBCode tag, returning the number of consumed characters.
+fn parse_bbcode_tag(chars: &[char]) -> Option<(usize, BBCodeTag)> {
+    let end = chars.iter().position(|&c| c == ']')?;
+    let tag: String = chars[1..end].iter().collect();
+    let tag = tag.trim_start_matches('/');
+
+    let (name, argument) = match tag.split_once('=') {
+        Some((name, argument)) => (name, Some(argument.trim_matches('"').to_string())),
+        None => (tag, None),
+    };
+    let name = name.to_ascii_lowercase();
+
+    if name == "url" && !chars[1..end].starts_with(&['/']) {
+        Some((end + 1, BBCodeTag::Url(argument)))
+    } else if name == "url" || BBCODE_FORMATTING_TAGS.contains(&name.as_str()) {
+        Some((end + 1, BBCodeTag::Formatting))
+    } else {
+        None
+    }
+}
+
+/// Get the content of a `[url]` tag up to its closing tag. The closing tag is
+/// left in place so that it is consumed as formatting.
+fn bbcode_inner(chars: &[char]) -> (String, usize) {
+    let rest: String = chars.iter().collect();
+    let end = rest
+        .to_ascii_lowercase()
+        .find("[/url]")
+        .unwrap_or(rest.len());
+    let inner = rest[..end].to_string();
+    let consumed = inner.chars().count();
+    (inner, consumed)
+}
+
+/// Decode the named and numeric HTML entities found in descriptions.
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| decode_html_entity(&rest[1..end]).map(|c| (c, end)));
+
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+fn decode_html_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "hellip" => Some('…'),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_description_plain() {
+        assert_eq!(sanitize_description("description"), "description");
+        assert_eq!(sanitize_description(""), "");
+    }
+
+    #[test]
+    fn test_sanitize_description_paragraphs() {
+        assert_eq!(
+            sanitize_description("first\r\n\r\n\r\nsecond  \nthird"),
+            "first\n\nsecond\nthird"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_description_formatting() {
+        assert_eq!(
+            sanitize_description("**bold** *italic* __under__ ~~strike~~ \\*escaped\\*"),
+            "bold italic under strike *escaped*"
+        );
+        assert_eq!(sanitize_description("## Header\n> quote"), "Header\nquote");
+        assert_eq!(sanitize_description("* one\n+ two"), "- one\n- two");
+        assert_eq!(sanitize_description("snake_case"), "snake_case");
+    }
+
+    #[test]
+    fn test_sanitize_description_bbcode() {
+        assert_eq!(
+            sanitize_description("[spoiler]hidden[/spoiler] and [b]bold[/b]"),
+            "hidden and bold"
+        );
+        assert_eq!(
+            sanitize_description("[url=https://example.com]site[/url]"),
+            "site\n\nLinks:\n- site: https://example.com"
+        );
+        assert_eq!(sanitize_description("[unknown]"), "[unknown]");
+    }
+
+    #[test]
+    fn test_sanitize_description_links() {
+        assert_eq!(
+            sanitize_description(
+                "Read [here](https://example.com/a_(b)) or [here](https://example.com/a_(b))."
+            ),
+            "Read here or here.\n\nLinks:\n- here: https://example.com/a_(b)"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_description_credits() {
+        let description = "A story.\n\n---\n**Links:**\n- [Official English](https://example.com/en)\n- Translated by someone";
+
+        assert_eq!(
+            sanitize_description(description),
+            "A story.\n\nLinks:\n- Official English: https://example.com/en"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_description_html_entities() {
+        assert_eq!(
+            sanitize_description("Tom &amp; Jerry &quot;&#39;&#x41;&quot; &unknown; & co"),
+            "Tom & Jerry \"'A\" &unknown; & co"
+        );
+    }
+}
@@ -1 +1,7 @@
+pub mod base64;
+pub mod chapter_number;
+pub mod chapter_title;
+pub mod clock;
+pub mod datetime;
+pub mod description;
 pub mod url_encode;
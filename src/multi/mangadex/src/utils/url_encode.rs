@@ -1,24 +1,25 @@
 pub fn url_encode(s: &str) -> String {
     let mut encoded = String::new();
-    for c in s.chars() {
-        match c {
-            'a'..='z'
-            | 'A'..='Z'
-            | '0'..='9'
-            | '-'
-            | '_'
-            | '.'
-            | '!'
-            | '~'
-            | '*'
-            | '\''
-            | '('
-            | ')' => {
-                encoded.push(c);
+    // Characters outside of ASCII are encoded byte by byte as UTF-8
+    for b in s.bytes() {
+        match b {
+            b'a'..=b'z'
+            | b'A'..=b'Z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => {
+                encoded.push(b as char);
             }
             _ => {
                 encoded.push('%');
-                encoded.push_str(&format!("{:02X}", c as u8));
+                encoded.push_str(&format!("{:02X}", b));
             }
         }
     }
@@ -37,5 +38,7 @@ mod tests {
         assert_eq!(url_encode("&"), "%26");
         assert_eq!(url_encode("+"), "%2B");
         assert_eq!(url_encode("?"), "%3F");
+        assert_eq!(url_encode("é"), "%C3%A9");
+        assert_eq!(url_encode("日本"), "%E6%97%A5%E6%9C%AC");
     }
 }